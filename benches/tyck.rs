@@ -0,0 +1,140 @@
+//! Type inference time on synthetic, deeply nested programs.
+//!
+//! Run with `cargo bench`. Each program is parsed and resolved once, then only
+//! the checkers are timed. The sizes double, so that the ratio between successive
+//! times shows how inference scales: about 2 for linear time, 4 for quadratic.
+
+use std::time::{Duration, Instant};
+use mujica_lang::frontend::desugar::desugar;
+use mujica_lang::frontend::hm::infer::HMChecker;
+use mujica_lang::frontend::name_resolution::{NameResolver, ResolvedProgram};
+use mujica_lang::frontend::parse;
+use mujica_lang::frontend::tyck::tyck::TypeChecker;
+
+const SIZES: [usize; 6] = [1000, 2000, 4000, 8000, 16000, 32000];
+const RUNS: u32 = 5;
+
+// The passes recurse once per level of nesting
+const STACK_SIZE: usize = 1 << 30;
+
+/// `let x1 = x0 in let x2 = x1 in ...`: every binding unifies with the previous one
+fn let_chain(n: usize) -> String {
+    let mut program = "let x0 = 1 in\n".to_string();
+    for i in 1..=n {
+        program.push_str(&format!("let x{} = x{} in\n", i, i - 1));
+    }
+
+    program.push_str(&format!("x{}\n", n));
+    program.push_str(&"end\n".repeat(n + 1));
+    program
+}
+
+/// Generalized functions, each calling the previous one: every use instantiates a long chain
+fn identity_chain(n: usize) -> String {
+    let mut program = "let f0 = fun x -> x in\n".to_string();
+    for i in 1..=n {
+        program.push_str(&format!("let f{} = fun x -> f{} x in\n", i, i - 1));
+    }
+
+    program.push_str(&format!("f{} 1\n", n));
+    program.push_str(&"end\n".repeat(n + 1));
+    program
+}
+
+/// `(fun x -> x) ((fun x -> x) (... 1))`: a deep expression whose type is only known at the bottom
+fn nested_applications(n: usize) -> String {
+    format!("{}1{}\n", "(fun x -> x) (".repeat(n), ")".repeat(n))
+}
+
+/// Lets inside a function, each unifying its value with the previous one: the variables
+/// of the chain form one long class, solved only when the function is applied
+fn unified_class(n: usize) -> String {
+    let mut program = "let same = fun a -> fun b -> a in\nlet f = fun x0 ->\n".to_string();
+    for i in 1..=n {
+        program.push_str(&format!("let x{} = same x{} x{} in\n", i, i - 1, i - 1));
+    }
+
+    program.push_str(&format!("x{}\n", n));
+    program.push_str(&"end\n".repeat(n));
+    program.push_str("in f 1\nend\nend\n");
+    program
+}
+
+/// Nested conditionals whose branches are unified pairwise
+fn nested_ifs(n: usize) -> String {
+    let mut program = "let b = true in\n".to_string();
+    for _ in 0..n {
+        program.push_str("if b then\n");
+    }
+
+    program.push('1');
+    for i in 0..n {
+        program.push_str(&format!("\nelse {} end", i));
+    }
+
+    program.push_str("\nend\n");
+    program
+}
+
+fn resolve(source: &str) -> ResolvedProgram {
+    let program = desugar(parse(source).expect("The benchmark program does not parse"));
+    NameResolver::new().resolve_program(program).expect("The benchmark program does not resolve")
+}
+
+/// The best time of `check` over a few runs, each on a fresh copy of `program`
+fn best(program: &ResolvedProgram, check: fn(ResolvedProgram)) -> Duration {
+    (0..RUNS).map(|_| {
+        let program = program.clone();
+        let start = Instant::now();
+        check(program);
+        start.elapsed()
+    }).min().unwrap_or_default()
+}
+
+fn bidirectional(program: ResolvedProgram) {
+    TypeChecker::new().tyck(program).expect("The benchmark program does not type check");
+}
+
+fn hm(program: ResolvedProgram) {
+    HMChecker::new().tyck(program).expect("The benchmark program does not type check");
+}
+
+fn run() {
+    let benchmarks: [(&str, fn(usize) -> String); 5] = [
+        ("let_chain", let_chain),
+        ("identity_chain", identity_chain),
+        ("nested_applications", nested_applications),
+        ("unified_class", unified_class),
+        ("nested_ifs", nested_ifs),
+    ];
+
+    let checkers: [(&str, fn(ResolvedProgram)); 2] = [("bidirectional", bidirectional), ("hm", hm)];
+
+    for (name, generate) in benchmarks {
+        for (checker, check) in checkers {
+            let mut previous: Option<Duration> = None;
+
+            for size in SIZES {
+                let program = resolve(&generate(size));
+                let time = best(&program, check);
+
+                let ratio = match previous {
+                    Some(previous) => format!("x{:.1}", time.as_secs_f64() / previous.as_secs_f64()),
+                    None => String::new(),
+                };
+                println!("{:<20} {:<14} n = {:<6} {:>10.2?} {:>6}", name, checker, size, time, ratio);
+
+                previous = Some(time);
+            }
+        }
+    }
+}
+
+fn main() {
+    std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("Failed to start the benchmark")
+        .join()
+        .expect("The benchmark failed");
+}
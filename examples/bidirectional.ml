@@ -0,0 +1,7 @@
+let apply : forall b. (b -> b) -> b -> b = fun f x -> f x in
+    let pick : Int -> Int = fun n ->
+        if n > 0 then apply (fun x -> x + 1) n else apply (fun x -> x * 2) 21 end
+    in
+        pick 0
+    end
+end
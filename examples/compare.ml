@@ -0,0 +1,7 @@
+let same : forall a. (Eq a) => a -> a -> Bool = fun x y -> x == y in
+    let max : forall a. (Ord a) => a -> a -> a = fun x y -> if x < y then y else x end in
+        if same true (3 >= 2) then
+            if same "mujica" "mujica" then max 4 7 else 0 end
+        else 0 - 1 end
+    end
+end
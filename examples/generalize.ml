@@ -0,0 +1,9 @@
+let id = fun (x: a) -> x in
+    let const = fun (x: b) (y: c) -> x in
+        let twice = fun (f: d -> d) (x: d) -> f (f x) in
+            let step = fun (n: Int) -> const (n + 1) false in
+                if id true then twice step (id 40) else 0 end
+            end
+        end
+    end
+end
@@ -0,0 +1,5 @@
+let inc = fun x -> x + 1 in
+    let choose = fun b x y -> if b then x else y end in
+        choose (inc 3 == 4) (inc 41) 0
+    end
+end
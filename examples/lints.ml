@@ -0,0 +1,6 @@
+let spin : int -> int = fun n -> if n > 0 then spin (n + 1) else spin (n - 1) end in
+let x = 40 in
+let unused = x * 2 in
+let add = fun x y -> x + y in
+if 1 < 2 then add x 2 else spin 0 end
+end end end end
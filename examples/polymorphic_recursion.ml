@@ -0,0 +1,11 @@
+let back : forall a. Int -> a -> a = fun n x ->
+    if n == 0 then x else
+        let unwrap = back (n - 1) (fun (y: a) -> y) in unwrap x end
+    end
+in
+    let depth : forall b. Int -> b -> Int = fun n x ->
+        if n == 0 then 0 else 1 + depth (n - 1) (fun (y: b) -> y) end
+    in
+        back 3 37 + depth 5 true
+    end
+end
@@ -0,0 +1,8 @@
+let id : forall a. a -> a =
+  fun (x: a) ->
+    let same : a -> a = fun (y: a) -> y in
+      same x
+    end
+in
+  id 42
+end
@@ -0,0 +1,17 @@
+class Show a where
+    show : a -> String
+end
+
+instance Show Int where
+    show = fun x -> "an integer"
+end
+
+instance Show Bool where
+    show = fun b -> if b then "true" else "false" end
+end
+
+let describe : forall a. (Show a) => a -> String = fun x -> show x in
+    let ignored = describe 42 in
+        describe (1 == 1)
+    end
+end
@@ -0,0 +1,7 @@
+type Pair a b;
+
+let apply_swap : forall a b. (Pair a b -> Pair b a) -> Pair a b -> Pair b a =
+  fun f p -> f p
+in
+  42
+end
@@ -25,6 +25,13 @@ pub enum ClosureCExpr {
         args: Vec<TypedAtom>,
         ty: Ty,
     },
+    /// A call of `func`, a variable bound to `closure`: its function is called directly
+    KnownApply {
+        closure: Rc<Closure>,
+        func: TypedAtom,
+        args: Vec<TypedAtom>,
+        ty: Ty,
+    },
     Closure(Rc<Closure>),
 }
 
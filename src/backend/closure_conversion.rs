@@ -1,7 +1,9 @@
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use crate::backend::closure::{Closure, ClosureCExpr, ClosureExpr};
-use crate::core::anf;
+use crate::core::{anf, Atom};
 use crate::core::anf::CExpr;
+use crate::frontend::name_resolution::NameIdentifier;
 use crate::util::name_generator::NameGenerator;
 
 #[derive(Debug)]
@@ -60,6 +62,51 @@ impl ClosureProgram {
         converted_expr
     }
     
+    /// Drop the functions that no closure reachable from `main` refers to
+    pub fn remove_unreferenced_globals(&mut self) {
+        let Some(main) = &self.main else { return };
+
+        let mut reachable = HashSet::new();
+        let mut pending = Vec::new();
+        referenced_closures(main, &mut pending);
+
+        while let Some(name) = pending.pop() {
+            if !reachable.insert(name.clone()) {
+                continue;
+            }
+
+            for global in &self.globals {
+                match global {
+                    ClosureProgramGlobal::FuncDef { closure, body } if closure.global_name == name => {
+                        referenced_closures(body, &mut pending);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        self.globals.retain(|global| match global {
+            ClosureProgramGlobal::FuncDef { closure, .. } => reachable.contains(&closure.global_name),
+        });
+    }
+
+    /// Call the function of a closure directly where the variable called is bound to it.
+    /// Identifiers are unique, so a captured variable is known by the binding it captures.
+    pub fn resolve_known_calls(&mut self) {
+        let mut known = HashMap::new();
+        for ClosureProgramGlobal::FuncDef { body, .. } in &self.globals {
+            known_closures(body, &mut known);
+        }
+        if let Some(main) = &self.main {
+            known_closures(main, &mut known);
+        }
+
+        for ClosureProgramGlobal::FuncDef { body, .. } in &mut self.globals {
+            *body = call_known(body.clone(), &known);
+        }
+        self.main = self.main.take().map(|main| call_known(main, &known));
+    }
+
     fn convert_cexpr(&mut self, cexpr: anf::CExpr) -> ClosureCExpr {
         match cexpr {
             anf::CExpr::Atom(atom) => ClosureCExpr::Atom(atom),
@@ -110,3 +157,73 @@ impl ClosureProgram {
         }
     }
 }
+
+/// The global names of the closures created in `expr`
+fn referenced_closures(expr: &ClosureExpr, names: &mut Vec<String>) {
+    let cexpr_closures = |cexpr: &ClosureCExpr, names: &mut Vec<String>| match cexpr {
+        ClosureCExpr::Closure(closure) => names.push(closure.global_name.clone()),
+        ClosureCExpr::If { then, else_, .. } => {
+            referenced_closures(then, names);
+            referenced_closures(else_, names);
+        }
+        ClosureCExpr::Atom(_) | ClosureCExpr::Apply { .. } | ClosureCExpr::KnownApply { .. } => {}
+    };
+
+    match expr {
+        ClosureExpr::CExpr(cexpr) => cexpr_closures(cexpr, names),
+        ClosureExpr::Let { value, body, .. } => {
+            cexpr_closures(value, names);
+            referenced_closures(body, names);
+        }
+    }
+}
+
+/// The closures bound by the lets of `expr`
+fn known_closures(expr: &ClosureExpr, known: &mut HashMap<NameIdentifier, Rc<Closure>>) {
+    let cexpr_closures = |cexpr: &ClosureCExpr, known: &mut HashMap<NameIdentifier, Rc<Closure>>| {
+        if let ClosureCExpr::If { then, else_, .. } = cexpr {
+            known_closures(then, known);
+            known_closures(else_, known);
+        }
+    };
+
+    match expr {
+        ClosureExpr::CExpr(cexpr) => cexpr_closures(cexpr, known),
+        ClosureExpr::Let { bind, value, body, .. } => {
+            if let ClosureCExpr::Closure(closure) = value.as_ref() {
+                known.insert(bind.id.clone(), closure.clone());
+            }
+
+            cexpr_closures(value, known);
+            known_closures(body, known);
+        }
+    }
+}
+
+fn call_known(expr: ClosureExpr, known: &HashMap<NameIdentifier, Rc<Closure>>) -> ClosureExpr {
+    let call_known_cexpr = |cexpr: ClosureCExpr| match cexpr {
+        ClosureCExpr::Apply { func, args, ty } => match &func.atom {
+            Atom::Var(var) if let Some(closure) = known.get(&var.id) && closure.args.len() == args.len() => {
+                ClosureCExpr::KnownApply { closure: closure.clone(), func, args, ty }
+            }
+            _ => ClosureCExpr::Apply { func, args, ty },
+        },
+        ClosureCExpr::If { cond, then, else_, ty } => ClosureCExpr::If {
+            cond,
+            then: Box::new(call_known(*then, known)),
+            else_: Box::new(call_known(*else_, known)),
+            ty,
+        },
+        cexpr => cexpr,
+    };
+
+    match expr {
+        ClosureExpr::CExpr(cexpr) => ClosureExpr::CExpr(call_known_cexpr(cexpr)),
+        ClosureExpr::Let { bind, value, body, ty } => ClosureExpr::Let {
+            bind,
+            value: Box::new(call_known_cexpr(*value)),
+            body: Box::new(call_known(*body, known)),
+            ty,
+        },
+    }
+}
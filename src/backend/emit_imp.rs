@@ -85,7 +85,11 @@ pub fn emit_imp(program: ClosureProgram) -> String {
         let main_body = main.emit_imp(&mut builder).unwrap().unwrap();
 
         // A temporary solution: print the result
-        builder.emit(format!("printf(\"%d\\n\", {});", main_body.name));
+        let format = match main_body.ty {
+            ImpType::Str => "%s",
+            _ => "%d",
+        };
+        builder.emit(format!("printf(\"{}\\n\", {});", format, main_body.name));
 
         // Close the main function
         builder.pop_scope();
@@ -190,21 +194,39 @@ impl EmitImp for ClosureCExpr {
                 match &func.atom {
                     Atom::Var(var) => {
                         let clos_var = builder.resolve_var(&var.id)?;
-                        
+
+                        // The signature of the function may differ from the types at the call
+                        // when it is a uniform implementation taking boxed values
+                        let mut params = Vec::new();
+                        let mut ret_ty = &func.ty;
+                        for arg in args {
+                            match ret_ty {
+                                Ty::Arrow(param, ret, _) => {
+                                    params.push(ImpType::from_type(param));
+                                    ret_ty = ret;
+                                }
+                                _ => params.push(ImpType::from_type(&arg.ty)),
+                            }
+                        }
+                        let ret_ty = ImpType::from_type(ret_ty);
+
                         let signature_fn = |name: &str| format!(
                             "{} (*{})(void*, {})",
-                            ImpType::from_type(&ty),
+                            ret_ty,
                             name,
-                            args.iter().map(|arg| format!("{}", ImpType::from_type(&arg.ty))).collect::<Vec<_>>().join(", ")
+                            params.iter().map(|param| param.to_string()).collect::<Vec<_>>().join(", ")
                         );
 
-                        builder.emit(format!("{} = (({}) {}->func)({}->env, {});",
-                                             result.name,
-                                             signature_fn(""),
-                                             clos_var.name,
-                                             clos_var.name,
-                                             call_args.iter().map(|arg| arg.name.clone()).collect::<Vec<_>>().join(", ")
-                        ));
+                        let call = format!("(({}) {}->func)({}->env, {})",
+                                           signature_fn(""),
+                                           clos_var.name,
+                                           clos_var.name,
+                                           call_args.iter().zip(&params)
+                                               .map(|(arg, param)| coerce(&arg.name, &arg.ty, param))
+                                               .collect::<Vec<_>>().join(", ")
+                        );
+
+                        builder.emit(format!("{} = {};", result.name, coerce(&call, &ret_ty, &result.ty)));
                     }
                     Atom::Op(op) => {
                         // Logic for applying an operator
@@ -225,22 +247,59 @@ impl EmitImp for ClosureCExpr {
                             OpType::Sub => { emit_binary_op!(builder, result, call_args, "-"); }
                             OpType::Mul => { emit_binary_op!(builder, result, call_args, "*"); }
                             OpType::Div => { emit_binary_op!(builder, result, call_args, "/"); }
-                            OpType::Eq => { emit_binary_op!(builder, result, call_args, "=="); }
-                            _ => unimplemented!(),
+                            OpType::Eq | OpType::Neq | OpType::Lt | OpType::Gt | OpType::Leq | OpType::Geq => {
+                                let c_op = match op {
+                                    OpType::Eq => "==",
+                                    OpType::Neq => "!=",
+                                    OpType::Lt => "<",
+                                    OpType::Gt => ">",
+                                    OpType::Leq => "<=",
+                                    _ => ">=",
+                                };
+
+                                // Monomorphization has fixed the instance, pick the comparison for it
+                                match call_args[0].ty {
+                                    ImpType::Str => builder.emit(format!("{} = strcmp({}, {}) {} 0;",
+                                                                         result.name,
+                                                                         call_args[0].name,
+                                                                         call_args[1].name,
+                                                                         c_op
+                                    )),
+                                    _ => emit_binary_op!(builder, result, call_args, c_op),
+                                }
+                            }
                         }
                     }
-                    Atom::Unit | Atom::Int(_) | Atom::Bool(_) => {
+                    Atom::Unit | Atom::Int(_) | Atom::Bool(_) | Atom::Str(_) | Atom::Hole(..) => {
                         return Err(BackendError::ImpError("Cannot apply non-function".to_string()));
                     }
                 }
 
                 Ok(result)
             }
+            ClosureCExpr::KnownApply { closure, func, args, ty } => {
+                let clos_var = func.emit_imp(builder)?;
+
+                // Arguments take the types of the parameters, boxed for a uniform implementation
+                let mut call_args = vec![format!("{}->env", clos_var.name)];
+                for (arg, param) in args.iter().zip(&closure.args) {
+                    let arg_var = arg.emit_imp(builder)?;
+                    call_args.push(coerce(&arg_var.name, &arg_var.ty, &ImpVar::from_typed_ident(param).ty));
+                }
+
+                let result = builder.fresh_imp_var(ImpType::from_type(ty));
+                builder.emit(format!("{} {};", result.ty, result.name));
+
+                let call = format!("{}({})", closure.global_name, call_args.join(", "));
+                builder.emit(format!("{} = {};", result.name, coerce(&call, &ImpType::from_type(&closure.ret_ty), &result.ty)));
+
+                Ok(result)
+            }
             ClosureCExpr::If { cond, then, else_, ty } => {
                 let phi_var = builder.fresh_imp_var(ImpType::from_type(ty));
                 builder.initialize_var(phi_var.clone()); // &*phi_var.name,
 
-                let cond_var = (TypedAtom { atom: cond.clone(), ty: Ty::Bool }).emit_imp(builder)?;
+                let cond_var = (TypedAtom { atom: cond.clone(), ty: Ty::Bool, ty_args: vec![] }).emit_imp(builder)?;
 
                 builder.emit(format!("if ({}) {{", cond_var.name));
                 builder.push_scope();
@@ -300,12 +359,30 @@ impl EmitImp for TypedAtom {
             }
             Atom::Op(_) => unreachable!(),
             Atom::Unit => unimplemented!(),
+            Atom::Hole(..) => unreachable!("Programs with holes are rejected by the type checker"),
             Atom::Bool(bool) => {
                 let imp_var = builder.fresh_imp_var(ImpType::Int);
                 builder.initialize_var(imp_var.clone());
                 builder.emit(format!("{} = {};", imp_var.name, if *bool { 1 } else { 0 }));
                 Ok(imp_var)
             }
+            Atom::Str(s) => {
+                let imp_var = builder.fresh_imp_var(ImpType::Str);
+                builder.initialize_var(imp_var.clone());
+                builder.emit(format!("{} = \"{}\";", imp_var.name, s));
+                Ok(imp_var)
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Convert the C expression `value` of type `from` to `to`. Only boxed values differ
+/// from their actual type: integers are stored in the pointer itself.
+fn coerce(value: &str, from: &ImpType, to: &ImpType) -> String {
+    match (from, to) {
+        _ if from == to => value.to_string(),
+        (ImpType::Int, _) => format!("({}) (intptr_t) {}", to, value),
+        (_, ImpType::Int) => format!("(int) (intptr_t) {}", value),
+        _ => format!("({}) {}", to, value),
+    }
+}
@@ -8,6 +8,7 @@ pub const CLOSURE_NAME: &str = "__closure";
 pub enum ImpType {
     Int,
     Void,
+    Str,
     Struct(String),
     Ptr(Box<ImpType>),
     ClosureContextOf(String),
@@ -24,12 +25,16 @@ impl ImpType {
             Ty::Int => ImpType::Int,
             Ty::Unit => ImpType::Void,
             Ty::Bool => ImpType::Int,
+            Ty::String => ImpType::Str,
             Ty::Arrow(..) => {
                 ImpType::Ptr(
                     Box::new(ImpType::ClosureStruct)
                 )
             }
-            Ty::Mono(..) => panic!("Cannot convert mono type to imp type"),
+            // Values of declared type constructors are opaque to the backend
+            Ty::App(..) => ImpType::void_ptr(),
+            Ty::Mono(..) | Ty::Var(..) => panic!("Cannot convert mono type to imp type"),
+            Ty::Forall(..) => panic!("Cannot convert polymorphic type to imp type"),
         }
     }
 }
@@ -39,6 +44,7 @@ impl Display for ImpType {
         let str = match self {
             ImpType::Int => "int".to_string(),
             ImpType::Void => "void".to_string(),
+            ImpType::Str => "char*".to_string(),
             ImpType::Struct(name) => format!("struct {}", name),
             ImpType::Ptr(ty) => format!("{}*", ty.to_string()),
             ImpType::ClosureContextOf(name) => format!("clos_env_{}", name),
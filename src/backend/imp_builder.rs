@@ -136,6 +136,8 @@ impl ImpBuilder {
         // C Libraries <stdio> and <stdlib.h>
         code.push_str("#include <stdio.h>\n");
         code.push_str("#include <stdlib.h>\n");
+        code.push_str("#include <stdint.h>\n");
+        code.push_str("#include <string.h>\n");
 
         // Globally, a closure is a function pointer (void *)
         // and a pointer to the closure context (also void *)
@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 use crate::core::{Atom, TypedAtom};
 use crate::frontend::name_resolution::ResolvedIdent;
-use crate::frontend::ty::Ty;
+use crate::frontend::ty::{Effect, Ty, TypeVar};
 use crate::util::pp::pretty_atom;
 
 #[derive(Debug, Clone)]
@@ -33,7 +33,7 @@ pub enum Expr {
         value: Box<CExpr>,
         body: Box<Expr>,
         ty: Ty,
-        is_polymorphic: bool,
+        ty_params: Vec<TypeVar>,
     },
 }
 
@@ -49,16 +49,67 @@ impl Expr {
             },
         }
     }
+
+    /// `let bind = self in body`, the bindings of `self` moved in front so that the result is in ANF.
+    /// Identifiers are unique, so they cannot capture variables of `body`.
+    pub fn bind_in(self, bind: ResolvedIdent, ty: Ty, body: Expr) -> Expr {
+        match self {
+            Expr::Let { bind: inner, value, body: rest, ty: inner_ty, ty_params } => Expr::Let {
+                bind: inner,
+                value,
+                body: Box::new(rest.bind_in(bind, ty, body)),
+                ty: inner_ty,
+                ty_params,
+            },
+            Expr::CExpr(value) => Expr::Let {
+                bind,
+                value: Box::new(value),
+                body: Box::new(body),
+                ty,
+                ty_params: vec![],
+            },
+        }
+    }
+
+    /// The effects of evaluating the expression: a pure expression whose value is unused can be dropped
+    pub fn effect(&self) -> Effect {
+        match self {
+            Expr::CExpr(cexpr) => cexpr.effect(),
+            Expr::Let { value, body, .. } => value.effect().union(body.effect()),
+        }
+    }
 }
 
 impl CExpr {
+    pub fn effect(&self) -> Effect {
+        match self {
+            CExpr::Atom(_) | CExpr::Lambda { .. } => Effect::PURE,
+            CExpr::If { then, else_, .. } => then.effect().union(else_.effect()),
+            // The function may call the functions passed to it
+            CExpr::Apply { func, args, .. } => args.iter()
+                .fold(func.ty.call_effect(args.len()), |effect, arg| effect.union(arg.ty.latent_effect())),
+        }
+    }
+
+    pub fn ty(&self) -> Ty {
+        match self {
+            CExpr::Atom(typed_atom) => typed_atom.ty.clone(),
+            CExpr::If { ty, .. } => ty.clone(),
+            CExpr::Apply { ty, .. } => ty.clone(),
+            CExpr::Lambda { args, ret_ty, .. } => args.iter().rfold(ret_ty.clone(), |acc, (_, ty)| {
+                Ty::Arrow(Box::new(ty.clone()), Box::new(acc), Effect::PURE)
+            }),
+        }
+    }
+
     pub fn free_vars(&self) -> HashSet<(ResolvedIdent, Ty)> {
         match self {
             CExpr::Atom(typed_atom) => typed_atom.free_vars(),
             CExpr::If { cond, then, else_, .. } => {
                 let mut vars = TypedAtom {
                     atom: cond.clone(),
-                    ty: Ty::Bool
+                    ty: Ty::Bool,
+                    ty_args: vec![],
                 }.free_vars();
                 
                 vars.extend(then.free_vars());
@@ -147,10 +198,14 @@ impl Expr {
     fn pretty_with_indent(expr: &Expr, indent: usize) -> String {
         match expr {
             Expr::CExpr(cexpr) => cexpr.pretty(indent),
-            Expr::Let { bind, value, body, ty: _, is_polymorphic } => {
+            Expr::Let { bind, value, body, ty: _, ty_params } => {
                 let value_str = CExpr::pretty(value, indent + 2);
                 let body_str = Self::pretty_with_indent(body, indent + 2);
-                let poly_marker = if *is_polymorphic { "poly " } else { "" };
+                let poly_marker = if ty_params.is_empty() {
+                    String::new()
+                } else {
+                    format!("Λ {}. ", ty_params.join(" "))
+                };
                 format!(
                     "let {}{} = {}\n{}in {}",
                     poly_marker,
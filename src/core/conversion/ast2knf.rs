@@ -20,7 +20,7 @@ impl AST2KNF {
         expr: uncurry::Expr,
     ) -> knf::Expr {
         match expr {
-            uncurry::Expr::Atom { atom, ty } => knf::Expr::Atom(TypedAtom { atom, ty}),
+            uncurry::Expr::Atom { atom, ty, ty_args } => knf::Expr::Atom(TypedAtom { atom, ty, ty_args }),
             uncurry::Expr::If { cond, then, else_, ty } => {
                 let cond = self.convert(*cond);
                 let then = self.convert(*then);
@@ -38,25 +38,25 @@ impl AST2KNF {
                         ty: ty.clone(),
                     }),
                     ty,
-                    is_polymorphic: false,
+                    ty_params: vec![],
                 }
             },
-            uncurry::Expr::Let { bind, value, body, ty, is_polymorphic } => knf::Expr::Let {
+            uncurry::Expr::Let { bind, value, body, ty, ty_params } => knf::Expr::Let {
                 bind: bind.clone(),
                 value: Box::new(self.convert(*value)),
                 body: Box::new(self.convert(*body)),
                 ty,
-                is_polymorphic,
+                ty_params,
             },
             uncurry::Expr::Apply { func, args, ty } => {
                 let func_ty = func.ty();
 
                 // 判断 func 是否是原子表达式
                 let (func_atom, func_let_opt) = match *func {
-                    uncurry::Expr::Atom { ref atom, ty: ref atom_ty } => {
+                    uncurry::Expr::Atom { ref atom, ty: ref atom_ty, ref ty_args } => {
                         // 是 atom，无需 let
                         (
-                            TypedAtom { atom: atom.clone(), ty: atom_ty.clone() },
+                            TypedAtom { atom: atom.clone(), ty: atom_ty.clone(), ty_args: ty_args.clone() },
                             None,
                         )
                     }
@@ -64,16 +64,17 @@ impl AST2KNF {
                         // 非 atom，必须 let-bind
                         let func_ident = self.name_generator.fresh_ident();
                         let atom = Atom::Var(func_ident.clone());
-                        let func_atom = TypedAtom { atom, ty: func_ty.clone() };
+                        let func_atom = TypedAtom { atom, ty: func_ty.clone(), ty_args: vec![] };
                         let func_let = knf::Expr::Let {
                             bind: func_ident,
                             value: Box::new(self.convert(*func)),
                             body: Box::new(knf::Expr::Atom(TypedAtom {
                                 atom: Atom::Unit,
-                                ty: Ty::Unit
+                                ty: Ty::Unit,
+                                ty_args: vec![],
                             })), // 占位，稍后补上
                             ty: func_ty.clone(),
-                            is_polymorphic: false,
+                            ty_params: vec![],
                         };
                         (func_atom, Some(func_let))
                     }
@@ -90,6 +91,7 @@ impl AST2KNF {
                     args: intermediate_vars.iter().map(|(var, ty)| TypedAtom {
                         atom: Atom::Var(var.clone()),
                         ty: ty.clone(),
+                        ty_args: vec![],
                     }).collect(),
                     ty: ty.clone(),
                 };
@@ -104,7 +106,7 @@ impl AST2KNF {
                             value: Box::new(self.convert(arg)),
                             body: Box::new(acc),
                             ty: ty.clone(),
-                            is_polymorphic: false,
+                            ty_params: vec![],
                         },
                     );
 
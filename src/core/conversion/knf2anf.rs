@@ -7,7 +7,7 @@ pub fn knf2anf(knf: knf::Expr) -> Result<anf::Expr, CoreError> {
 
 fn knf2anf_impl(knf: knf::Expr, k: Box<dyn FnOnce(anf::CExpr) -> Result<anf::Expr, CoreError>>) -> Result<anf::Expr, CoreError> {
     match knf {
-        knf::Expr::Let { bind, value, body, ty, is_polymorphic } => {
+        knf::Expr::Let { bind, value, body, ty, ty_params } => {
             let bind_clone = bind.clone();
 
             Ok(
@@ -19,7 +19,7 @@ fn knf2anf_impl(knf: knf::Expr, k: Box<dyn FnOnce(anf::CExpr) -> Result<anf::Exp
                             value: Box::new(c),
                             body: Box::from(knf2anf_impl(*body, k)?),
                             ty,
-                            is_polymorphic,
+                            ty_params,
                         })
                     })
                 )?
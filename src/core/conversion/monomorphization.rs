@@ -1,12 +1,57 @@
 use std::collections::{HashMap, HashSet};
-use crate::core::{anf, Atom, TypedAtom};
+use crate::core::{anf, Atom, CoreError, TypedAtom};
 use crate::core::anf::CExpr;
 use crate::frontend::name_resolution::{NameIdentifier, ResolvedIdent};
-use crate::frontend::ty::{Ty, TypeVar};
+use crate::frontend::ty::{boxed_ty, Ty, TypeVar};
+use crate::frontend::tyck::type_class::MethodTable;
+use crate::util::name_generator::NameGenerator;
+
+/// Rounds of instances requesting further instances of the same binding before
+/// its instantiation is considered unbounded, as with polymorphic recursion
+const MAX_INSTANTIATION_DEPTH: usize = 16;
 
 pub struct Monomorphization {
-    pub instances: HashMap<NameIdentifier, HashMap<Vec<Ty>, (ResolvedIdent, Ty)>>,
+    /// Instances requested so far for each polymorphic binding in scope,
+    /// from the type arguments to the identifier of the specialized binding
+    pub instances: HashMap<NameIdentifier, HashMap<Vec<Ty>, ResolvedIdent>>,
     pub polymorphic: HashSet<NameIdentifier>,
+    /// Polymorphic bindings without a finite set of instances, compiled to uniform implementations
+    pub boxed: HashMap<NameIdentifier, Uniform>,
+    pub methods: MethodTable,
+    // Names of the functions adapting a uniform implementation to the type of a use
+    name_generator: NameGenerator,
+}
+
+/// A polymorphically recursive binding, whose type variables that grow with each recursive
+/// call are boxed. The others take finitely many types and are specialized as usual,
+/// so there is one uniform implementation for each of their instantiations.
+pub struct Uniform {
+    bind: ResolvedIdent,
+    ty_params: Vec<TypeVar>,
+    // Whether each type parameter is specialized rather than boxed
+    fixed: Vec<bool>,
+    value_ty: Ty,
+    // The number of arguments the implementation takes
+    arity: usize,
+    // The specialization of the enclosing definitions
+    update: UpdateMap,
+    /// The implementation for each instantiation of the fixed parameters, with its type
+    instances: HashMap<Vec<Ty>, (ResolvedIdent, Ty)>,
+}
+
+impl Uniform {
+    /// The types the parameters stand for in the implementation for `key`
+    fn specialization(&self, key: &[Ty]) -> UpdateMap {
+        let mut key = key.iter();
+        let mut update = self.update.clone();
+
+        for (param, fixed) in self.ty_params.iter().zip(&self.fixed) {
+            let ty = if *fixed { key.next().unwrap().clone() } else { boxed_ty() };
+            update.insert(param.clone(), ty);
+        }
+
+        update
+    }
 }
 
 pub type UpdateMap = HashMap<TypeVar, Ty>;
@@ -20,186 +65,313 @@ fn apply_update(ty: Ty, update: &UpdateMap) -> Ty {
                 Ty::Mono(var)
             }
         }
-        Ty::Arrow(left, right) => {
+        Ty::Arrow(left, right, effect) => {
             Ty::Arrow(
                 Box::new(apply_update(*left, update)),
                 Box::new(apply_update(*right, update)),
+                effect,
             )
         }
+        Ty::App(name, args) => Ty::App(
+            name,
+            args.into_iter().map(|arg| apply_update(arg, update)).collect(),
+        ),
         _ => ty,
     }
 }
 
-fn bind(update: &mut UpdateMap, var: &TypeVar, ty: Ty) {
-    update.insert(var.clone(), ty);
-}
-
 impl Monomorphization {
     pub fn new() -> Self {
         Monomorphization {
             instances: HashMap::new(),
             polymorphic: HashSet::new(),
+            boxed: HashMap::new(),
+            methods: MethodTable::default(),
+            name_generator: NameGenerator::new("uniform_"),
+        }
+    }
+
+    /// Class method calls are resolved to the implementations in `methods`
+    pub fn with_methods(methods: MethodTable) -> Self {
+        Monomorphization {
+            methods,
+            ..Self::new()
         }
     }
-    
-    pub fn make_mono_ident(base: &ResolvedIdent, args: &[Ty]) -> ResolvedIdent {
-        let mangled = args.iter().map(|ty| ty.mangle()).
-            collect::<Vec<_>>()
-            .join("_");
+
+    pub fn make_mono_ident(base: &ResolvedIdent, ty: &Ty) -> ResolvedIdent {
+        let mangled = ty.mangle();
         let name = format!("{}__{}", base.name, mangled);
         let id = format!("{}__{}", base.id.0, mangled);
-        
+
         ResolvedIdent::new(name, id)
     }
 
-    pub fn collect_instances(&mut self, expr: &anf::Expr) {
+    /// Request the specialization of a polymorphic binding at monomorphic type arguments,
+    /// for a use of type `ty`
+    fn request_instance(&mut self, var: &ResolvedIdent, ty_args: &[Ty], ty: &Ty) -> Result<ResolvedIdent, CoreError> {
+        if ty_args.iter().any(|arg| !arg.free_vars().is_empty()) {
+            return Err(CoreError::ConversionError(format!(
+                "Cannot monomorphize {} at non-monomorphic type {}",
+                var.name, ty
+            )));
+        }
+
+        Ok(self.instances
+            .entry(var.id.clone())
+            .or_default()
+            .entry(ty_args.to_vec())
+            .or_insert_with(|| Self::make_mono_ident(var, ty))
+            .clone())
+    }
+
+    /// Higher-rank arguments are checked by the type checker, but have no monomorphic
+    /// specialization: a function taking a polymorphic argument would need every instance at once
+    pub fn check_rank1(expr: &anf::Expr) -> Result<(), CoreError> {
         match expr {
-            anf::Expr::Let { bind, value, body, is_polymorphic, .. } => {
-                if *is_polymorphic {
-                    self.polymorphic.insert(bind.id.clone());
-                }
-                
-                self.collect_cexpr(&value);
-                self.collect_instances(body);
+            anf::Expr::Let { value, body, .. } => {
+                Self::check_rank1_cexpr(value)?;
+                Self::check_rank1(body)
             }
-            anf::Expr::CExpr(cexpr) => self.collect_cexpr(&cexpr),
+            anf::Expr::CExpr(cexpr) => Self::check_rank1_cexpr(cexpr),
         }
     }
 
-    fn collect_cexpr(&mut self, cexpr: &CExpr) {
+    fn check_rank1_cexpr(cexpr: &CExpr) -> Result<(), CoreError> {
         match cexpr {
-            CExpr::If { cond: _cond, then, else_, .. } => {
-                self.collect_instances(then);
-                self.collect_instances(else_);
+            CExpr::Lambda { args, body, .. } => {
+                if let Some((ident, ty)) = args.iter().find(|(_, ty)| ty.is_higher_rank()) {
+                    return Err(CoreError::ConversionError(format!(
+                        "Cannot compile argument {} of higher-rank type {}: \
+                         higher-rank polymorphism is only supported by the type checker",
+                        ident.name, ty
+                    )));
+                }
+
+                Self::check_rank1(body)
             }
-            CExpr::Apply { func, args: _args, .. } => {
-                // match &func.atom {
-                //     Atom::Var(var) => {
-                //         let arg_types: Vec<Ty> = args.iter().map(|arg| arg.ty.clone()).collect();
-                //         
-                //         println!("Collecting polymorphic instance for {} with args {:?}", var, arg_types);
-                //         
-                //         // self.instances
-                //         //     .entry(var.id.clone())
-                //         //     .or_default()
-                //         //     .push((arg_types, make_mono_ident(id, &arg_types)));
-                //     }
-                //     _ => {} // No polymorphism in other types of atom
-                // }
-                self.collect_typed_atom(func);
+            CExpr::If { then, else_, .. } => {
+                Self::check_rank1(then)?;
+                Self::check_rank1(else_)
             }
-            CExpr::Lambda { args: _args, body, .. } => {
-                // Collect instances for the body of the lambda
-                self.collect_instances(body);
+            CExpr::Apply { .. } | CExpr::Atom(_) => Ok(()),
+        }
+    }
+
+    pub fn rewrite_expr(&mut self, expr: anf::Expr, update: &UpdateMap) -> Result<anf::Expr, CoreError> {
+        match expr {
+            anf::Expr::Let { bind, value, body, ty, ty_params } if !ty_params.is_empty() => {
+                self.polymorphic.insert(bind.id.clone());
+
+                // Each visit of the binding (e.g. inside different instances of an
+                // enclosing function) specializes only for the uses it reaches
+                let outer_instances = self.instances.remove(&bind.id);
+
+                // Restored if the instances turn out to be unbounded
+                let saved_instances = self.instances.clone();
+                let original_body = body.clone();
+
+                // The uses in the body decide which instances are needed
+                let mut new_body = self.rewrite_expr(*body, update)?;
+
+                let mut done = HashSet::new();
+
+                // An instance may request further instances, e.g. through recursion
+                for depth in 0.. {
+                    let pending: Vec<(Vec<Ty>, ResolvedIdent)> = self.instances
+                        .get(&bind.id)
+                        .map(|insts| insts.iter()
+                            .filter(|(ty_args, _)| !done.contains(*ty_args))
+                            .map(|(ty_args, mono_id)| (ty_args.clone(), mono_id.clone()))
+                            .collect())
+                        .unwrap_or_default();
+
+                    if pending.is_empty() {
+                        break;
+                    }
+
+                    if depth == MAX_INSTANTIATION_DEPTH {
+                        // Polymorphic recursion: every instance requests a bigger one
+                        self.instances = saved_instances;
+                        let boxed = self.rewrite_boxed(bind.clone(), &ty_params, *value, *original_body, ty, update)?;
+
+                        match outer_instances {
+                            Some(insts) => { self.instances.insert(bind.id.clone(), insts); }
+                            None => { self.instances.remove(&bind.id); }
+                        }
+
+                        return Ok(boxed);
+                    }
+
+                    for (ty_args, mono_id) in pending {
+                        // The type parameters of the binding stand for the type arguments
+                        let mut inst_update = update.clone();
+                        inst_update.extend(ty_params.iter().cloned().zip(ty_args.iter().cloned()));
+
+                        let new_value = self.rewrite_cexpr((*value).clone(), &inst_update)?;
+
+                        new_body = new_value.bind_in(mono_id, apply_update(ty.clone(), update), new_body);
+
+                        done.insert(ty_args);
+                    }
+                }
+
+                match outer_instances {
+                    Some(insts) => { self.instances.insert(bind.id.clone(), insts); }
+                    None => { self.instances.remove(&bind.id); }
+                }
+
+                Ok(new_body)
             }
-            CExpr::Atom(typed_atom) => {
-                self.collect_typed_atom(typed_atom);
+            anf::Expr::Let { bind, value, body, ty, .. } => {
+                let value = self.rewrite_cexpr(*value, update)?;
+                let body = self.rewrite_expr(*body, update)?;
+
+                Ok(value.bind_in(bind, apply_update(ty, update), body))
             }
+            anf::Expr::CExpr(cexpr) => self.rewrite_cexpr(cexpr, update),
         }
     }
 
-    fn collect_typed_atom(&mut self, typed_atom: &TypedAtom) {
-        match &typed_atom.atom {
-            Atom::Var(var) => {
-                let (arg_types, ret_ty) = typed_atom.ty.extract_args();
+    /// Compile a polymorphic binding to uniform implementations, in which its type variables
+    /// that grow through recursion are boxed. Every use of the binding is a call of one of them.
+    fn rewrite_boxed(
+        &mut self,
+        bind: ResolvedIdent,
+        ty_params: &[TypeVar],
+        value: CExpr,
+        body: anf::Expr,
+        ty: Ty,
+        update: &UpdateMap,
+    ) -> Result<anf::Expr, CoreError> {
+        let arity = match &value {
+            CExpr::Lambda { args, .. } => args.len(),
+            _ => 0,
+        };
+
+        self.boxed.insert(bind.id.clone(), Uniform {
+            bind: bind.clone(),
+            ty_params: ty_params.to_vec(),
+            fixed: fixed_params(&bind.id, ty_params, &value),
+            value_ty: value.ty(),
+            arity,
+            update: update.clone(),
+            instances: HashMap::new(),
+        });
 
-                let mono_ident = Self::make_mono_ident(&var, &arg_types);
+        let mut new_body = self.rewrite_expr(body, update)?;
+        let mut done = HashSet::new();
 
-                println!("Collecting instance for {} with args {:?}, assigning to {:?}",
-                         var.id.0, arg_types, mono_ident);
+        // An implementation may call the one for other fixed types
+        loop {
+            let pending: Vec<(Vec<Ty>, ResolvedIdent)> = self.boxed[&bind.id].instances.iter()
+                .filter(|(key, _)| !done.contains(*key))
+                .map(|(key, (uniform_ident, _))| (key.clone(), uniform_ident.clone()))
+                .collect();
 
-                self.instances
-                    .entry(var.id.clone())
-                    .or_default()
-                    .insert(arg_types, (mono_ident, ret_ty));
+            if pending.is_empty() {
+                break;
+            }
+
+            for (key, uniform_ident) in pending {
+                let uniform_update = self.boxed[&bind.id].specialization(&key);
+                let new_value = self.rewrite_cexpr(value.clone(), &uniform_update)?;
+
+                new_body = new_value.bind_in(uniform_ident, apply_update(ty.clone(), update), new_body);
+                done.insert(key);
             }
-            _ => {}
         }
+
+        self.boxed.remove(&bind.id);
+
+        Ok(new_body)
     }
 
-    pub fn rewrite_expr(&self, expr: anf::Expr, update: &mut UpdateMap) -> anf::Expr {
-        fn unify(a: Ty, b: Ty, update: &mut UpdateMap) {
-            let a = apply_update(a, update);
-            let b = apply_update(b, update);
+    /// The uniform implementation of `var` for a use at the type arguments `ty_args`, and its type
+    fn request_uniform(&mut self, var: &ResolvedIdent, ty_args: &[Ty]) -> (ResolvedIdent, Ty) {
+        let uniform = self.boxed.get_mut(&var.id).unwrap();
 
-            match (a, b) {
-                (Ty::Int, Ty::Int) | (Ty::Bool, Ty::Bool) => {},
-                (Ty::Mono(ref x), t) | (t, Ty::Mono(ref x)) => bind(update, x, t),
-                (Ty::Arrow(a1, a2), Ty::Arrow(b1, b2)) => {
-                    unify(*a1, *b1, update);
-                    unify(*a2, *b2, update);
-                }
-                (a, b) => panic!(
-                    "Cannot unify types: {} and {}",
-                    a, b
-                ),
-            }
+        let key: Vec<Ty> = ty_args.iter()
+            .zip(&uniform.fixed)
+            .filter(|(_, fixed)| **fixed)
+            .map(|(ty_arg, _)| ty_arg.clone())
+            .collect();
+
+        if let Some(found) = uniform.instances.get(&key) {
+            return found.clone();
         }
-        
-        match expr {
-            anf::Expr::Let { bind, value, body, ty, is_polymorphic } => {
-                if let CExpr::Lambda { args, body: lam_body, ret_ty } = value.as_ref() {
-                    if is_polymorphic {
-                        // Polymorphic function, wrap the body with multiple instances
-                        let mut new_body = self.rewrite_expr(*body, update);
-
-                        let insts = self.instances.get(&bind.id).unwrap();
-
-                        for (arg_types, (mono_id, actual_ret_ty)) in insts.iter() {
-                            let new_args: Vec<(ResolvedIdent, Ty)> = args.iter()
-                                .zip(arg_types)
-                                .map(|((name, _), t)| (name.clone(), t.clone()))
-                                .collect();
-                            
-                            update.clear();
-
-                            for ((_, inst_ty), (_, arg_ty)) in new_args.iter().zip(args) {
-                                // Update the type variable mapping
-                                unify(arg_ty.clone(), inst_ty.clone(), update);
-                            }
-
-                            println!("Update hashmap is {:?}", update);
-
-                            let new_lambda = CExpr::Lambda {
-                                args: new_args,
-                                body: Box::new(self.rewrite_expr(*lam_body.clone(), update)),
-                                ret_ty: actual_ret_ty.clone(),
-                            };
-
-                            new_body = anf::Expr::Let {
-                                bind: mono_id.clone(),
-                                value: Box::new(new_lambda),
-                                body: Box::new(new_body),
-                                ty: apply_update(ty.clone(), update),
-                                is_polymorphic: false
-                            };
-                        }
 
-                        return new_body;
-                    }
-                }
-                
-                // Not polymorphic, rewrite normally
-                anf::Expr::Let {
-                    bind: bind.clone(),
-                    value: Box::new(self.rewrite_cexpr(*value, update)),
-                    body: Box::new(self.rewrite_expr(*body, update)),
-                    ty: apply_update(ty, update),
-                    is_polymorphic: false,
-                }
+        let uniform_ty = apply_update(uniform.value_ty.clone(), &uniform.specialization(&key));
+        let uniform_ident = Self::make_mono_ident(&uniform.bind, &uniform_ty);
+        uniform.instances.insert(key, (uniform_ident.clone(), uniform_ty.clone()));
+
+        (uniform_ident, uniform_ty)
+    }
+
+    /// A function of type `ty` calling the uniform implementation of `var`, for a use of
+    /// it as a value: its callers pass unboxed arguments and expect an unboxed result
+    fn adapter(&mut self, var: &ResolvedIdent, ty_args: &[Ty], ty: Ty) -> CExpr {
+        let (uniform_ident, uniform_ty) = self.request_uniform(var, ty_args);
+
+        let mut args = Vec::new();
+        let mut ret_ty = ty;
+        for _ in 0..self.boxed[&var.id].arity {
+            let Ty::Arrow(param, ret, _) = ret_ty else { break };
+
+            args.push((ResolvedIdent::new("x".to_string(), self.name_generator.next_name()), *param));
+            ret_ty = *ret;
+        }
+
+        let call = CExpr::Apply {
+            func: TypedAtom { atom: Atom::Var(uniform_ident), ty: uniform_ty, ty_args: vec![] },
+            args: args.iter()
+                .map(|(arg, ty)| TypedAtom { atom: Atom::Var(arg.clone()), ty: ty.clone(), ty_args: vec![] })
+                .collect(),
+            ty: ret_ty.clone(),
+        };
+
+        CExpr::Lambda { args, body: Box::new(anf::Expr::CExpr(call)), ret_ty }
+    }
+
+    /// The atom `typed_atom` as a value: a use of a uniform implementation is bound to its adapter
+    fn rewrite_value_atom(
+        &mut self,
+        typed_atom: TypedAtom,
+        update: &UpdateMap,
+        adapters: &mut Vec<(ResolvedIdent, CExpr)>,
+    ) -> Result<TypedAtom, CoreError> {
+        match &typed_atom.atom {
+            Atom::Var(var) if self.boxed.contains_key(&var.id) => {
+                let ty = apply_update(typed_atom.ty, update);
+                let adapter = self.adapter(var, &update_all(&typed_atom.ty_args, update), ty.clone());
+                let adapter_ident = ResolvedIdent::new(var.name.clone(), self.name_generator.next_name());
+                adapters.push((adapter_ident.clone(), adapter));
+
+                Ok(TypedAtom { atom: Atom::Var(adapter_ident), ty, ty_args: vec![] })
             }
-            anf::Expr::CExpr(cexpr) => anf::Expr::CExpr(self.rewrite_cexpr(cexpr, update)),
+            _ => self.rewrite_typed_atom(typed_atom, update),
         }
     }
 
-    fn rewrite_cexpr(&self, cexpr: CExpr, update: &mut UpdateMap) -> CExpr {
-        match cexpr {
+    /// The specialization of `cexpr`, preceded by the bindings of the adapters it uses
+    fn rewrite_cexpr(&mut self, cexpr: CExpr, update: &UpdateMap) -> Result<anf::Expr, CoreError> {
+        let mut adapters = Vec::new();
+
+        let cexpr = match cexpr {
             CExpr::Apply { func, args, ty } => {
-                let func = self.rewrite_typed_atom(func, update);
+                // A boxed function is called at its uniform type, the backend boxes
+                // and unboxes the arguments and the result
+                let func = match &func.atom {
+                    Atom::Var(var) if self.boxed.contains_key(&var.id) => {
+                        let (uniform_ident, uniform_ty) = self.request_uniform(var, &update_all(&func.ty_args, update));
+                        TypedAtom { atom: Atom::Var(uniform_ident), ty: uniform_ty, ty_args: vec![] }
+                    }
+                    _ => self.rewrite_typed_atom(func, update)?,
+                };
                 let args = args.into_iter()
-                    .map(|arg| self.rewrite_typed_atom(arg, update))
-                    .collect();
-                
+                    .map(|arg| self.rewrite_value_atom(arg, update, &mut adapters))
+                    .collect::<Result<_, _>>()?;
+
                 CExpr::Apply {
                     func,
                     args,
@@ -208,46 +380,130 @@ impl Monomorphization {
             }
             CExpr::If { cond, then, else_, ty } => CExpr::If {
                 cond,
-                then: Box::new(self.rewrite_expr(*then, update)),
-                else_: Box::new(self.rewrite_expr(*else_, update)),
+                then: Box::new(self.rewrite_expr(*then, update)?),
+                else_: Box::new(self.rewrite_expr(*else_, update)?),
                 ty: apply_update(ty, update),
             },
+            CExpr::Atom(TypedAtom { atom: Atom::Var(var), ty, ty_args }) if self.boxed.contains_key(&var.id) => {
+                self.adapter(&var, &update_all(&ty_args, update), apply_update(ty, update))
+            }
             CExpr::Atom(typed_atom) => {
-                let rewritten_atom = self.rewrite_typed_atom(typed_atom, update);
+                let rewritten_atom = self.rewrite_typed_atom(typed_atom, update)?;
                 CExpr::Atom(rewritten_atom)
             }
             CExpr::Lambda { args, body, ret_ty } => {
-                let rewritten_body = self.rewrite_expr(*body, update);
-                
+                let rewritten_body = self.rewrite_expr(*body, update)?;
+
                 CExpr::Lambda {
-                    args,
+                    args: args.into_iter()
+                        .map(|(ident, ty)| (ident, apply_update(ty, update)))
+                        .collect(),
                     body: Box::new(rewritten_body),
-                    ret_ty: apply_update(ret_ty, update), // TODO
+                    ret_ty: apply_update(ret_ty, update),
                 }
             }
-        }
+        };
+
+        Ok(adapters.into_iter().rev().fold(anf::Expr::CExpr(cexpr), |body, (adapter_ident, adapter)| {
+            let ty = adapter.ty();
+            anf::Expr::CExpr(adapter).bind_in(adapter_ident, ty, body)
+        }))
     }
-    
-    fn rewrite_typed_atom(&self, typed_atom: TypedAtom, update: &mut UpdateMap) -> TypedAtom {
-        match &typed_atom.atom {
-            Atom::Var(var) => {
-                if let Some(insts) = self.instances.get(&var.id) {
-                    if self.polymorphic.contains(&var.id) {
-                        let (new_ident, _) = insts.get(&typed_atom.ty.extract_args().0).unwrap();
-                        
-                        return TypedAtom {
-                            atom: Atom::Var(new_ident.clone()),
-                            ty: apply_update(typed_atom.ty, update),
-                        };
-                    }
-                }
+
+    fn rewrite_typed_atom(&mut self, typed_atom: TypedAtom, update: &UpdateMap) -> Result<TypedAtom, CoreError> {
+        let ty = apply_update(typed_atom.ty, update);
+
+        match typed_atom.atom {
+            Atom::Var(var) if self.methods.is_method(&var.id) => {
+                // The instance is known once the type is monomorphic
+                let impl_ident = self.methods.resolve(&var.id, &ty).ok_or_else(|| CoreError::ConversionError(format!(
+                    "No instance of method {} at type {}",
+                    var.name, ty
+                )))?;
+
+                Ok(TypedAtom {
+                    atom: Atom::Var(impl_ident),
+                    ty,
+                    ty_args: vec![],
+                })
             }
-            _ => {}
+            Atom::Var(var) if self.polymorphic.contains(&var.id) => {
+                let ty_args = update_all(&typed_atom.ty_args, update);
+
+                Ok(TypedAtom {
+                    atom: Atom::Var(self.request_instance(&var, &ty_args, &ty)?),
+                    ty,
+                    ty_args: vec![],
+                })
+            }
+            atom => Ok(TypedAtom { atom, ty, ty_args: vec![] }),
+        }
+    }
+}
+
+fn update_all(tys: &[Ty], update: &UpdateMap) -> Vec<Ty> {
+    tys.iter().map(|ty| apply_update(ty.clone(), update)).collect()
+}
+
+/// Which type parameters of a polymorphically recursive binding take finitely many types:
+/// those its recursive uses instantiate to closed types, or to such parameters. This
+/// includes every parameter with a class constraint, since instances are of closed types.
+/// The other parameters grow with each recursive call.
+fn fixed_params(id: &NameIdentifier, ty_params: &[TypeVar], value: &CExpr) -> Vec<bool> {
+    let mut uses = Vec::new();
+    uses_in_cexpr(value, id, &mut uses);
+
+    let mut fixed = vec![true; ty_params.len()];
+
+    loop {
+        let grows = |ty: &Ty, fixed: &[bool]| ty.free_vars().iter().any(|var| {
+            ty_params.iter()
+                .position(|param| param == var)
+                .is_some_and(|j| !fixed[j] || !matches!(ty, Ty::Mono(_)))
+        });
+
+        let growing: Vec<usize> = (0..ty_params.len())
+            .filter(|&i| fixed[i] && uses.iter().any(|ty_args| ty_args.get(i).is_some_and(|arg| grows(arg, &fixed))))
+            .collect();
+
+        if growing.is_empty() {
+            return fixed;
+        }
+
+        for i in growing {
+            fixed[i] = false;
         }
-        
-        TypedAtom {
-            atom: typed_atom.atom,
-            ty: apply_update(typed_atom.ty, update),
+    }
+}
+
+/// The type arguments of the uses of `id` in `expr`
+fn uses_in(expr: &anf::Expr, id: &NameIdentifier, uses: &mut Vec<Vec<Ty>>) {
+    match expr {
+        anf::Expr::Let { value, body, .. } => {
+            uses_in_cexpr(value, id, uses);
+            uses_in(body, id, uses);
         }
+        anf::Expr::CExpr(cexpr) => uses_in_cexpr(cexpr, id, uses),
     }
-}
\ No newline at end of file
+}
+
+fn uses_in_cexpr(cexpr: &CExpr, id: &NameIdentifier, uses: &mut Vec<Vec<Ty>>) {
+    match cexpr {
+        CExpr::Atom(typed_atom) => uses.extend(use_of(typed_atom, id)),
+        CExpr::Apply { func, args, .. } => {
+            uses.extend(std::iter::once(func).chain(args).filter_map(|typed_atom| use_of(typed_atom, id)));
+        }
+        CExpr::If { then, else_, .. } => {
+            uses_in(then, id, uses);
+            uses_in(else_, id, uses);
+        }
+        CExpr::Lambda { body, .. } => uses_in(body, id, uses),
+    }
+}
+
+fn use_of(typed_atom: &TypedAtom, id: &NameIdentifier) -> Option<Vec<Ty>> {
+    match &typed_atom.atom {
+        Atom::Var(var) if var.id == *id => Some(typed_atom.ty_args.clone()),
+        _ => None,
+    }
+}
@@ -1,6 +1,6 @@
 use crate::core::{Atom, TypedAtom};
 use crate::frontend::name_resolution::ResolvedIdent;
-use crate::frontend::ty::Ty;
+use crate::frontend::ty::{Ty, TypeVar};
 use crate::util::pp::{pretty_atom, pretty_op};
 
 pub enum Expr {
@@ -16,7 +16,7 @@ pub enum Expr {
         value: Box<Expr>,
         body: Box<Expr>,
         ty: Ty,
-        is_polymorphic: bool,
+        ty_params: Vec<TypeVar>,
     },
     Apply {
         func: TypedAtom,
@@ -6,16 +6,27 @@ pub mod uncurry;
 pub mod conversion;
 pub mod knf;
 pub mod anf;
+pub mod optimize;
 
 #[derive(Debug)]
 pub enum CoreError {
     ConversionError(String), // Represents an error during conversion
 }
 
+impl std::fmt::Display for CoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoreError::ConversionError(message) => write!(f, "{}", message),
+        }
+    }
+}
+
 pub type Atom = ASTAtom<ResolvedIdent>;
 
 #[derive(Debug, Clone)]
 pub struct TypedAtom {
     pub atom: Atom,
     pub ty: Ty,
+    /// The type arguments of a polymorphic variable, empty for every other atom
+    pub ty_args: Vec<Ty>,
 }
\ No newline at end of file
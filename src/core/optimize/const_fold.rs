@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use crate::core::anf::{CExpr, Expr};
+use crate::core::{Atom, TypedAtom};
+use crate::frontend::ast::OpType;
+use crate::frontend::name_resolution::{NameIdentifier, ResolvedIdent};
+use crate::frontend::ty::{Ty, TypeVar};
+
+/// Evaluate what is known at compile time: operators applied to literals are
+/// computed, variables bound to literals are replaced by them, and conditionals
+/// on a literal are replaced by the branch taken. Arithmetic that would overflow
+/// or divide by zero is left to run.
+pub fn fold_constants(expr: Expr) -> Expr {
+    ConstantFolding { constants: HashMap::new() }.expr(expr)
+}
+
+struct ConstantFolding {
+    // Variables bound to a literal, identifiers are unique so scopes never clash
+    constants: HashMap<NameIdentifier, Atom>,
+}
+
+impl ConstantFolding {
+    fn expr(&mut self, expr: Expr) -> Expr {
+        match expr {
+            Expr::Let { bind, value, body, ty, ty_params } => {
+                let value = self.cexpr(*value);
+                self.bind(bind, value, *body, ty, ty_params)
+            }
+            Expr::CExpr(cexpr) => self.cexpr(cexpr),
+        }
+    }
+
+    /// Bind the folded `value` in `body`. A value that folded to a literal is propagated,
+    /// one that folded to a branch has its bindings moved in front of the let.
+    fn bind(&mut self, bind: ResolvedIdent, value: Expr, body: Expr, ty: Ty, ty_params: Vec<TypeVar>) -> Expr {
+        match value {
+            Expr::Let { bind: inner, value: inner_value, body: rest, ty: inner_ty, ty_params: inner_params } => Expr::Let {
+                bind: inner,
+                value: inner_value,
+                body: Box::new(self.bind(bind, *rest, body, ty, ty_params)),
+                ty: inner_ty,
+                ty_params: inner_params,
+            },
+            Expr::CExpr(CExpr::Atom(TypedAtom { atom, .. })) if is_literal(&atom) => {
+                self.constants.insert(bind.id, atom);
+                self.expr(body)
+            }
+            Expr::CExpr(value) => Expr::Let {
+                bind,
+                value: Box::new(value),
+                body: Box::new(self.expr(body)),
+                ty,
+                ty_params,
+            },
+        }
+    }
+
+    fn cexpr(&mut self, cexpr: CExpr) -> Expr {
+        let folded = match cexpr {
+            CExpr::Atom(atom) => CExpr::Atom(self.atom(atom)),
+            CExpr::If { cond, then, else_, ty } => match self.propagate(cond) {
+                Atom::Bool(true) => return self.expr(*then),
+                Atom::Bool(false) => return self.expr(*else_),
+                cond => CExpr::If {
+                    cond,
+                    then: Box::new(self.expr(*then)),
+                    else_: Box::new(self.expr(*else_)),
+                    ty,
+                },
+            },
+            CExpr::Apply { func, args, ty } => {
+                let args: Vec<TypedAtom> = args.into_iter().map(|arg| self.atom(arg)).collect();
+
+                match (&func.atom, args.as_slice()) {
+                    (Atom::Op(op), [left, right]) if let Some(result) = evaluate(op, &left.atom, &right.atom) => {
+                        CExpr::Atom(TypedAtom { atom: result, ty, ty_args: vec![] })
+                    }
+                    _ => CExpr::Apply { func: self.atom(func), args, ty },
+                }
+            }
+            CExpr::Lambda { args, body, ret_ty } => CExpr::Lambda {
+                args,
+                body: Box::new(self.expr(*body)),
+                ret_ty,
+            },
+        };
+
+        Expr::CExpr(folded)
+    }
+
+    fn atom(&self, typed_atom: TypedAtom) -> TypedAtom {
+        TypedAtom { atom: self.propagate(typed_atom.atom), ..typed_atom }
+    }
+
+    fn propagate(&self, atom: Atom) -> Atom {
+        match &atom {
+            Atom::Var(var) => self.constants.get(&var.id).cloned().unwrap_or(atom),
+            _ => atom,
+        }
+    }
+}
+
+/// Literals that are cheap to copy into every use
+fn is_literal(atom: &Atom) -> bool {
+    matches!(atom, Atom::Int(_) | Atom::Bool(_))
+}
+
+/// The result of `left op right`, if both are literals it is defined on
+fn evaluate(op: &OpType, left: &Atom, right: &Atom) -> Option<Atom> {
+    match (left, right) {
+        (Atom::Int(l), Atom::Int(r)) => Some(match op {
+            OpType::Add => Atom::Int(l.checked_add(*r)?),
+            OpType::Sub => Atom::Int(l.checked_sub(*r)?),
+            OpType::Mul => Atom::Int(l.checked_mul(*r)?),
+            OpType::Div => Atom::Int(l.checked_div(*r)?),
+            OpType::Eq => Atom::Bool(l == r),
+            OpType::Neq => Atom::Bool(l != r),
+            OpType::Lt => Atom::Bool(l < r),
+            OpType::Gt => Atom::Bool(l > r),
+            OpType::Leq => Atom::Bool(l <= r),
+            OpType::Geq => Atom::Bool(l >= r),
+        }),
+        (Atom::Bool(l), Atom::Bool(r)) => match op {
+            OpType::Eq => Some(Atom::Bool(l == r)),
+            OpType::Neq => Some(Atom::Bool(l != r)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
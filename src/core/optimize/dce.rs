@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+use crate::core::anf::{CExpr, Expr};
+use crate::core::{Atom, TypedAtom};
+use crate::frontend::ast::OpType;
+use crate::frontend::name_resolution::NameIdentifier;
+
+/// Remove the let bindings whose variable is never used and whose value has no effect,
+/// e.g. temporaries left over by earlier passes and specializations nothing calls.
+/// A division is kept unless it is by a nonzero literal, since dividing by zero fails.
+pub fn eliminate_dead_code(expr: Expr) -> Expr {
+    DeadCodeElimination { params: HashSet::new() }.expr(expr).0
+}
+
+struct DeadCodeElimination {
+    // Function parameters in scope. Effect inference assumes they are pure and accounts
+    // for them at the call of the enclosing function, so calling one is kept.
+    params: HashSet<NameIdentifier>,
+}
+
+impl DeadCodeElimination {
+    /// The expression without its dead bindings, and the variables it uses
+    fn expr(&mut self, expr: Expr) -> (Expr, HashSet<NameIdentifier>) {
+        match expr {
+            Expr::Let { bind, value, body, ty, ty_params } => {
+                let (body, mut used) = self.expr(*body);
+
+                if !used.contains(&bind.id) && self.is_pure(&value) {
+                    return (body, used);
+                }
+
+                let (value, value_used) = self.cexpr(*value);
+                used.extend(value_used);
+                used.remove(&bind.id);
+
+                (Expr::Let { bind, value: Box::new(value), body: Box::new(body), ty, ty_params }, used)
+            }
+            Expr::CExpr(cexpr) => {
+                let (cexpr, used) = self.cexpr(cexpr);
+                (Expr::CExpr(cexpr), used)
+            }
+        }
+    }
+
+    fn cexpr(&mut self, cexpr: CExpr) -> (CExpr, HashSet<NameIdentifier>) {
+        match cexpr {
+            CExpr::Atom(typed_atom) => {
+                let used = uses(&typed_atom.atom).collect();
+                (CExpr::Atom(typed_atom), used)
+            }
+            CExpr::If { cond, then, else_, ty } => {
+                let (then, mut used) = self.expr(*then);
+                let (else_, else_used) = self.expr(*else_);
+                used.extend(else_used);
+                used.extend(uses(&cond));
+
+                (CExpr::If { cond, then: Box::new(then), else_: Box::new(else_), ty }, used)
+            }
+            CExpr::Apply { func, args, ty } => {
+                let used = std::iter::once(&func).chain(&args).flat_map(|arg| uses(&arg.atom)).collect();
+                (CExpr::Apply { func, args, ty }, used)
+            }
+            CExpr::Lambda { args, body, ret_ty } => {
+                self.params.extend(args.iter().map(|(ident, _)| ident.id.clone()));
+                let (body, mut used) = self.expr(*body);
+
+                for (ident, _) in &args {
+                    used.remove(&ident.id);
+                }
+
+                (CExpr::Lambda { args, body: Box::new(body), ret_ty }, used)
+            }
+        }
+    }
+
+    fn is_pure(&self, value: &CExpr) -> bool {
+        value.effect().is_pure() && !self.has_unrecorded_effect(value)
+    }
+
+    /// Whether `value` calls a parameter or may divide by zero, effects its type does not record
+    fn has_unrecorded_effect(&self, value: &CExpr) -> bool {
+        match value {
+            CExpr::Apply { func: TypedAtom { atom: Atom::Var(var), .. }, .. } => self.params.contains(&var.id),
+            CExpr::Apply { func: TypedAtom { atom: Atom::Op(OpType::Div), .. }, args, .. } =>
+                !matches!(args.as_slice(), [_, TypedAtom { atom: Atom::Int(divisor), .. }] if *divisor != 0),
+            CExpr::If { then, else_, .. } => self.has_unrecorded_effect_expr(then) || self.has_unrecorded_effect_expr(else_),
+            _ => false,
+        }
+    }
+
+    fn has_unrecorded_effect_expr(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Let { value, body, .. } => self.has_unrecorded_effect(value) || self.has_unrecorded_effect_expr(body),
+            Expr::CExpr(cexpr) => self.has_unrecorded_effect(cexpr),
+        }
+    }
+}
+
+fn uses(atom: &Atom) -> impl Iterator<Item = NameIdentifier> {
+    match atom {
+        Atom::Var(var) => Some(var.id.clone()),
+        _ => None,
+    }.into_iter()
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use crate::core::anf::{CExpr, Expr};
+use crate::core::{Atom, TypedAtom};
+use crate::frontend::name_resolution::{NameIdentifier, ResolvedIdent};
+use crate::frontend::ty::{Ty, BOXED};
+use crate::util::name_generator::NameGenerator;
+
+/// Replace the calls of small known functions with their bodies. A let-bound lambda whose
+/// body has at most `budget` expressions is inlined where it is applied to all of its
+/// arguments, unless it calls itself. Run on monomorphic ANF.
+pub fn inline(expr: Expr, budget: usize) -> Expr {
+    let mut inliner = Inliner {
+        budget,
+        known: HashMap::new(),
+        name_generator: NameGenerator::new("inl_"),
+    };
+
+    inliner.expr(expr)
+}
+
+struct Inliner {
+    budget: usize,
+    // Functions that may be inlined: their parameters and body, already optimized
+    known: HashMap<NameIdentifier, (Vec<(ResolvedIdent, Ty)>, Expr)>,
+    name_generator: NameGenerator,
+}
+
+impl Inliner {
+    fn expr(&mut self, expr: Expr) -> Expr {
+        match expr {
+            Expr::Let { bind, value, body, ty, .. } => {
+                let value = self.cexpr(*value);
+
+                if let Expr::CExpr(CExpr::Lambda { args, body: lambda_body, ret_ty }) = &value
+                    && self.is_inlinable(&bind, args, lambda_body, ret_ty)
+                {
+                    self.known.insert(bind.id.clone(), (args.clone(), (**lambda_body).clone()));
+                }
+
+                // A copy of a known function, as ANF makes of every argument
+                if let Expr::CExpr(CExpr::Atom(TypedAtom { atom: Atom::Var(var), .. })) = &value
+                    && let Some(function) = self.known.get(&var.id).cloned()
+                {
+                    self.known.insert(bind.id.clone(), function);
+                }
+
+                let body = self.expr(*body);
+                value.bind_in(bind, ty, body)
+            }
+            Expr::CExpr(cexpr) => self.cexpr(cexpr),
+        }
+    }
+
+    fn cexpr(&mut self, cexpr: CExpr) -> Expr {
+        match cexpr {
+            CExpr::Apply { func: TypedAtom { atom: Atom::Var(func), .. }, args, .. }
+                if matches!(self.known.get(&func.id), Some((params, _)) if params.len() == args.len()) =>
+            {
+                let (params, body) = self.known[&func.id].clone();
+
+                // The parameters stand for the arguments, with the types and effects of the
+                // arguments, and the bindings of the body get new names
+                let mut renaming = Renaming {
+                    args: params.into_iter().map(|(param, _)| param.id).zip(args).collect(),
+                    binds: HashMap::new(),
+                };
+                let body = self.rename(body, &mut renaming);
+
+                // Calls of the arguments may now be to known functions
+                self.expr(body)
+            }
+            CExpr::If { cond, then, else_, ty } => Expr::CExpr(CExpr::If {
+                cond,
+                then: Box::new(self.expr(*then)),
+                else_: Box::new(self.expr(*else_)),
+                ty,
+            }),
+            CExpr::Lambda { args, body, ret_ty } => Expr::CExpr(CExpr::Lambda {
+                args,
+                body: Box::new(self.expr(*body)),
+                ret_ty,
+            }),
+            cexpr => Expr::CExpr(cexpr),
+        }
+    }
+
+    /// Small, not recursive, and not a uniform implementation, whose boxed
+    /// parameters differ from the arguments it is called with. A function with a
+    /// higher-rank parameter is kept for `check_rank1` to reject if it is called.
+    fn is_inlinable(&self, bind: &ResolvedIdent, args: &[(ResolvedIdent, Ty)], body: &Expr, ret_ty: &Ty) -> bool {
+        size(body) <= self.budget
+            && !mentions(body, &bind.id)
+            && !args.iter().map(|(_, ty)| ty).chain([ret_ty]).any(mentions_boxed)
+            && !args.iter().any(|(_, ty)| ty.is_higher_rank())
+    }
+
+    fn rename(&mut self, expr: Expr, renaming: &mut Renaming) -> Expr {
+        match expr {
+            Expr::Let { bind, value, body, ty, ty_params } => {
+                let value = self.rename_cexpr(*value, renaming);
+                let bind = self.fresh(bind, renaming);
+
+                Expr::Let {
+                    bind,
+                    value: Box::new(value),
+                    body: Box::new(self.rename(*body, renaming)),
+                    ty,
+                    ty_params,
+                }
+            }
+            Expr::CExpr(cexpr) => Expr::CExpr(self.rename_cexpr(cexpr, renaming)),
+        }
+    }
+
+    fn rename_cexpr(&mut self, cexpr: CExpr, renaming: &mut Renaming) -> CExpr {
+        match cexpr {
+            CExpr::Atom(typed_atom) => CExpr::Atom(renaming.typed_atom(typed_atom)),
+            CExpr::If { cond, then, else_, ty } => CExpr::If {
+                cond: renaming.atom(cond),
+                then: Box::new(self.rename(*then, renaming)),
+                else_: Box::new(self.rename(*else_, renaming)),
+                ty,
+            },
+            CExpr::Apply { func, args, ty } => CExpr::Apply {
+                func: renaming.typed_atom(func),
+                args: args.into_iter().map(|arg| renaming.typed_atom(arg)).collect(),
+                ty,
+            },
+            CExpr::Lambda { args, body, ret_ty } => {
+                let args = args.into_iter().map(|(arg, ty)| (self.fresh(arg, renaming), ty)).collect();
+
+                CExpr::Lambda {
+                    args,
+                    body: Box::new(self.rename(*body, renaming)),
+                    ret_ty,
+                }
+            }
+        }
+    }
+
+    /// A new identifier for the binding `ident`, keeping its name
+    fn fresh(&mut self, ident: ResolvedIdent, renaming: &mut Renaming) -> ResolvedIdent {
+        let fresh = ResolvedIdent::new(ident.name, self.name_generator.next_name());
+        renaming.binds.insert(ident.id, fresh.clone());
+        fresh
+    }
+}
+
+/// What the variables of an inlined body become
+struct Renaming {
+    // Parameters, replaced by the whole argument: a function argument brings the
+    // effects of calling it, which the parameter's type does not have
+    args: HashMap<NameIdentifier, TypedAtom>,
+    // Bindings of the body, given new identifiers
+    binds: HashMap<NameIdentifier, ResolvedIdent>,
+}
+
+impl Renaming {
+    fn typed_atom(&self, typed_atom: TypedAtom) -> TypedAtom {
+        match &typed_atom.atom {
+            Atom::Var(var) if let Some(arg) = self.args.get(&var.id) => arg.clone(),
+            _ => TypedAtom { atom: self.atom(typed_atom.atom), ..typed_atom },
+        }
+    }
+
+    fn atom(&self, atom: Atom) -> Atom {
+        match &atom {
+            Atom::Var(var) if let Some(arg) = self.args.get(&var.id) => arg.atom.clone(),
+            Atom::Var(var) if let Some(fresh) = self.binds.get(&var.id) => Atom::Var(fresh.clone()),
+            _ => atom,
+        }
+    }
+}
+
+/// The number of expressions in `expr`, including those of nested functions
+fn size(expr: &Expr) -> usize {
+    match expr {
+        Expr::Let { value, body, .. } => size_cexpr(value) + size(body),
+        Expr::CExpr(cexpr) => size_cexpr(cexpr),
+    }
+}
+
+fn size_cexpr(cexpr: &CExpr) -> usize {
+    match cexpr {
+        CExpr::Atom(_) | CExpr::Apply { .. } => 1,
+        CExpr::If { then, else_, .. } => 1 + size(then) + size(else_),
+        CExpr::Lambda { body, .. } => 1 + size(body),
+    }
+}
+
+/// Whether `id` occurs in `expr`
+fn mentions(expr: &Expr, id: &NameIdentifier) -> bool {
+    let atom_mentions = |atom: &Atom| matches!(atom, Atom::Var(var) if var.id == *id);
+
+    let cexpr_mentions = |cexpr: &CExpr| match cexpr {
+        CExpr::Atom(typed_atom) => atom_mentions(&typed_atom.atom),
+        CExpr::If { cond, then, else_, .. } => atom_mentions(cond) || mentions(then, id) || mentions(else_, id),
+        CExpr::Apply { func, args, .. } => atom_mentions(&func.atom) || args.iter().any(|arg| atom_mentions(&arg.atom)),
+        CExpr::Lambda { body, .. } => mentions(body, id),
+    };
+
+    match expr {
+        Expr::Let { value, body, .. } => cexpr_mentions(value) || mentions(body, id),
+        Expr::CExpr(cexpr) => cexpr_mentions(cexpr),
+    }
+}
+
+fn mentions_boxed(ty: &Ty) -> bool {
+    match ty {
+        Ty::App(name, args) => name == BOXED || args.iter().any(mentions_boxed),
+        Ty::Arrow(left, right, _) => mentions_boxed(left) || mentions_boxed(right),
+        Ty::Forall(_, body) => mentions_boxed(body),
+        _ => false,
+    }
+}
@@ -0,0 +1,3 @@
+pub mod const_fold;
+pub mod dce;
+pub mod inline;
@@ -1,14 +1,16 @@
 use crate::core::CoreError;
-use crate::frontend::ast::{ASTAtom, ASTExpr};
+use crate::frontend::ast::ASTAtom;
 use crate::frontend::name_resolution::ResolvedIdent;
-use crate::frontend::ty::Ty;
-use crate::frontend::tyck::tyck::TypedASTExpr;
+use crate::frontend::ty::{Effect, Ty, TypeVar};
+use crate::frontend::tyck::elaborate::ElabExpr;
 
 #[derive(Debug)]
 pub enum Expr {
     Atom {
         atom: ASTAtom<ResolvedIdent>,
-        ty: Ty
+        ty: Ty,
+        /// The type arguments of a polymorphic variable
+        ty_args: Vec<Ty>,
     },
     If {
         cond: Box<Expr>,
@@ -21,7 +23,8 @@ pub enum Expr {
         value: Box<Expr>,
         body: Box<Expr>,
         ty: Ty,
-        is_polymorphic: bool, // Indicates if the let binding is polymorphic
+        /// The type variables a polymorphic binding abstracts over, none if it is monomorphic
+        ty_params: Vec<TypeVar>,
     },
     Apply {
         func: Box<Expr>,
@@ -42,37 +45,52 @@ impl Expr {
             Expr::If { ty, .. } => ty.clone(),
             Expr::Let { ty, .. } => ty.clone(),
             Expr::Apply { ty, .. } => ty.clone(),
-            Expr::Lambda { ret_ty, .. } => ret_ty.clone(),
+            Expr::Lambda { args, ret_ty, .. } => args.iter().rfold(ret_ty.clone(), |acc, (_, ty)| {
+                Ty::Arrow(Box::new(ty.clone()), Box::new(acc), Effect::PURE)
+            }),
         }
     }
 }
 
-pub fn uncurry(ast: TypedASTExpr) -> Result<Expr, CoreError> {
-    match ast {
-        ASTExpr::Atom(atom, ty) => Ok(Expr::Atom { atom, ty }),
+pub fn uncurry(expr: ElabExpr) -> Result<Expr, CoreError> {
+    match expr {
+        ElabExpr::Atom { atom, ty } => Ok(Expr::Atom { atom, ty, ty_args: vec![] }),
+
+        ElabExpr::TyApp { var, ty_args, ty } => Ok(Expr::Atom { atom: ASTAtom::Var(var), ty, ty_args }),
 
-        ASTExpr::If { cond, then, else_, ty } => Ok(Expr::If {
+        ElabExpr::If { cond, then, else_, ty } => Ok(Expr::If {
             cond: Box::new(uncurry(*cond)?),
             then: Box::new(uncurry(*then)?),
             else_: Box::new(uncurry(*else_)?),
             ty,
         }),
 
-        ASTExpr::Let { bind: (id, scheme), value, body, ty } => Ok(Expr::Let {
-            bind: id,
-            value: Box::new(uncurry(*value)?),
-            body: Box::new(uncurry(*body)?),
-            ty,
-            is_polymorphic: scheme.is_some()
-        }),
+        ElabExpr::Let { bind, value, body, ty } => {
+            let (ty_params, value) = match *value {
+                ElabExpr::TyAbs { params, body } => (params, *body),
+                value => (vec![], value),
+            };
+
+            Ok(Expr::Let {
+                bind,
+                value: Box::new(uncurry(value)?),
+                body: Box::new(uncurry(*body)?),
+                ty,
+                ty_params,
+            })
+        }
+
+        ElabExpr::TyAbs { .. } => Err(CoreError::ConversionError(
+            "A type abstraction may only be the value of a let binding".to_string()
+        )),
 
-        ASTExpr::Apply { func, args, ty } => {
+        ElabExpr::Apply { func, arg, ty } => {
             let mut func_expr = *func;
-            let mut arg_list = vec![*args];
+            let mut arg_list = vec![*arg];
 
             // Flatten curried apply chain
-            while let ASTExpr::Apply { func, args, ty: _ } = func_expr {
-                arg_list.push(*args);
+            while let ElabExpr::Apply { func, arg, ty: _ } = func_expr {
+                arg_list.push(*arg);
                 func_expr = *func;
             }
 
@@ -84,13 +102,13 @@ pub fn uncurry(ast: TypedASTExpr) -> Result<Expr, CoreError> {
             })
         }
 
-        ASTExpr::Lambda { arg, body, ret_ty } => {
+        ElabExpr::Lambda { arg, body, ret_ty } => {
             let mut args = vec![arg];
             let mut curr_body = *body;
             let mut final_ret_ty = ret_ty.clone();
 
             // Flatten nested lambdas
-            while let ASTExpr::Lambda { arg, body, ret_ty } = curr_body {
+            while let ElabExpr::Lambda { arg, body, ret_ty } = curr_body {
                 args.push(arg);
                 curr_body = *body;
                 final_ret_ty = ret_ty;
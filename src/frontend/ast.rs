@@ -1,38 +1,127 @@
+use std::fmt;
+use std::fmt::Display;
 use crate::frontend::FrontendError;
-use crate::frontend::ty::{Scheme, Ty};
+use crate::frontend::ty::{Effect, Scheme, Ty, TypeVar};
 
-#[derive(Debug)]
+/// Where an expression starts in the source, as 1-based line and column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Span {
+    /// Locate the byte offset `offset` of `src`
+    pub fn new(src: &str, offset: usize) -> Self {
+        let before = &src[..offset];
+
+        Span {
+            line: before.matches('\n').count() + 1,
+            col: before.chars().rev().take_while(|c| *c != '\n').count() + 1,
+        }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum ASTExpr<I, T, S = Option<Scheme>> {
-    Atom(ASTAtom<I>, T),
+    /// An atom with its type. The type checker records the type arguments of a variable
+    /// of polymorphic type, the types its scheme is instantiated to; empty for every other atom
+    Atom(ASTAtom<I>, T, Vec<Ty>),
     If {
         cond: Box<ASTExpr<I, T>>,
         then: Box<ASTExpr<I, T>>,
         else_: Box<ASTExpr<I, T>>,
         ty: T,
+        span: Span,
     },
     Let {
         bind: (I, S),
         value: Box<ASTExpr<I, T>>,
         body: Box<ASTExpr<I, T>>,
         ty: T,
+        /// The type variables the value abstracts over, set by the type checker on a
+        /// polymorphic binding: the rigid variables of its annotation, or those it generalizes
+        ty_params: Vec<TypeVar>,
+        span: Span,
     },
     /// Single argument function application
     Apply {
         func: Box<ASTExpr<I, T>>,
         args: Box<ASTExpr<I, T>>,
         ty: T,
+        span: Span,
     },
     /// Single argument lambda expression
     Lambda {
         arg: (I, T),
         body: Box<ASTExpr<I, T>>,
         ret_ty: T,
+        span: Span,
     },
 }
 
+impl<I, S> ASTExpr<I, Ty, S> {
+    /// The type of a typed expression, a lambda's built from its parameter and result
+    pub fn ty(&self) -> Ty {
+        match self {
+            ASTExpr::Atom(_, ty, _) => ty.clone(),
+            ASTExpr::If { ty, .. } | ASTExpr::Let { ty, .. } | ASTExpr::Apply { ty, .. } => ty.clone(),
+            ASTExpr::Lambda { arg: (_, arg_ty), ret_ty, .. } => {
+                Ty::Arrow(Box::new(arg_ty.clone()), Box::new(ret_ty.clone()), Effect::PURE)
+            }
+        }
+    }
+}
+
 // Use `String` as identifier type and `Option<Ty>` for type annotations
 pub type InputASTExpr = ASTExpr<String, Option<Ty>>;
 
+/// `class Show a where show : a -> String end`
+#[derive(Debug, Clone)]
+pub struct ClassDecl<I> {
+    pub name: String,
+    pub type_var: TypeVar,
+    pub methods: Vec<(I, Ty)>,
+}
+
+/// `instance Show Int where show = fun x -> "int" end`
+#[derive(Debug, Clone)]
+pub struct InstanceDecl<I, T> {
+    pub class: String,
+    pub ty: Ty,
+    pub methods: Vec<(I, ASTExpr<I, T>)>,
+}
+
+/// `type List a;` declares a type constructor taking one argument
+#[derive(Debug, Clone)]
+pub struct TypeDecl {
+    pub name: String,
+    pub params: Vec<TypeVar>,
+}
+
+/// Top-level declarations, in source order
+#[derive(Debug, Clone)]
+pub enum Decl<I, T> {
+    Type(TypeDecl),
+    Class(ClassDecl<I>),
+    Instance(InstanceDecl<I, T>),
+}
+
+/// A whole source file: declarations followed by the main expression
+#[derive(Debug, Clone)]
+pub struct Program<I, T> {
+    pub decls: Vec<Decl<I, T>>,
+    pub body: ASTExpr<I, T>,
+}
+
+pub type InputProgram = Program<String, Option<Ty>>;
+
 #[derive(Debug, Clone)]
 pub enum ASTAtom<I> {
     Int(i32),
@@ -40,6 +129,10 @@ pub enum ASTAtom<I> {
     Op(OpType),
     Unit,
     Bool(bool),
+    /// String literal, kept with its escape sequences as written in the source
+    Str(String),
+    /// Typed hole `_` or `?name` at a location, reported by the type checker instead of being compiled
+    Hole(Option<String>, Span),
 }
 
 impl<I> ASTAtom<I> {
@@ -84,18 +177,3 @@ pub enum OpType {
     Leq,
     Geq,
 }
-
-#[macro_export]
-macro_rules! curry_binop {
-    ($op:expr, $l:expr, $r:expr) => {
-        InputASTExpr::Apply {
-            func: Box::new(InputASTExpr::Apply {
-                func: Box::new(InputASTExpr::Atom(ASTAtom::Op($op), None)),
-                args: Box::new($l),
-                ty: None,
-            }),
-            args: Box::new($r),
-            ty: None, // Type can be inferred later
-        }
-    };
-}
\ No newline at end of file
@@ -0,0 +1,70 @@
+use crate::frontend::ast::{ASTAtom, Decl, InputASTExpr, InputProgram, InstanceDecl, Program};
+use crate::frontend::surface::{SurfaceDecl, SurfaceExpr, SurfaceInstanceDecl, SurfaceProgram};
+
+/// Lower a parsed program to the core AST, where every function takes a single argument
+pub fn desugar(program: SurfaceProgram) -> InputProgram {
+    let decls = program.decls.into_iter().map(|decl| match decl {
+        SurfaceDecl::Type(decl) => Decl::Type(decl),
+        SurfaceDecl::Class(decl) => Decl::Class(decl),
+        SurfaceDecl::Instance(SurfaceInstanceDecl { class, ty, methods }) => Decl::Instance(InstanceDecl {
+            class,
+            ty,
+            methods: methods.into_iter().map(|(name, value)| (name, desugar_expr(value))).collect(),
+        }),
+    }).collect();
+
+    Program { decls, body: desugar_expr(program.body) }
+}
+
+/// - `l op r` becomes `(op l) r`
+/// - `f a b` becomes `(f a) b`
+/// - `fun x y -> e` becomes `fun x -> fun y -> e`
+/// - Parentheses are dropped
+pub fn desugar_expr(expr: SurfaceExpr) -> InputASTExpr {
+    match expr {
+        SurfaceExpr::Atom(atom) => InputASTExpr::Atom(atom, None, vec![]),
+        SurfaceExpr::Paren(expr) => desugar_expr(*expr),
+        SurfaceExpr::If { cond, then, else_, span } => InputASTExpr::If {
+            cond: Box::new(desugar_expr(*cond)),
+            then: Box::new(desugar_expr(*then)),
+            else_: Box::new(desugar_expr(*else_)),
+            ty: None,
+            span,
+        },
+        SurfaceExpr::Let { bind, value, body, span } => InputASTExpr::Let {
+            bind,
+            value: Box::new(desugar_expr(*value)),
+            body: Box::new(desugar_expr(*body)),
+            ty: None,
+            ty_params: vec![],
+            span,
+        },
+        SurfaceExpr::Apply { func, args, span } => {
+            args.into_iter().fold(desugar_expr(*func), |func, arg| InputASTExpr::Apply {
+                func: Box::new(func),
+                args: Box::new(desugar_expr(arg)),
+                ty: None,
+                span,
+            })
+        }
+        SurfaceExpr::BinOp { op, left, right, span } => InputASTExpr::Apply {
+            func: Box::new(InputASTExpr::Apply {
+                func: Box::new(InputASTExpr::Atom(ASTAtom::Op(op), None, vec![])),
+                args: Box::new(desugar_expr(*left)),
+                ty: None,
+                span,
+            }),
+            args: Box::new(desugar_expr(*right)),
+            ty: None,
+            span,
+        },
+        SurfaceExpr::Lambda { args, body, span } => {
+            args.into_iter().rfold(desugar_expr(*body), |body, arg| InputASTExpr::Lambda {
+                arg,
+                body: Box::new(body),
+                ret_ty: None,
+                span,
+            })
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use crate::frontend::ast::{ASTAtom, ASTExpr};
+use crate::frontend::name_resolution::NameIdentifier;
+use crate::frontend::ty::{Effect, Ty};
+use crate::frontend::tyck::tyck::TypedASTExpr;
+use crate::frontend::tyck::type_class::MethodTable;
+
+/// Infer the effects of the functions of a typed program and annotate its arrow types with them.
+///
+/// Every expression gets a type whose arrows carry the effects of calling it, and the effects
+/// of evaluating it. Parameters of function type are assumed pure: a function passed as an
+/// argument is accounted for at the call, whose effects and result include everything the
+/// argument may do when called. The only effect the language can have so far is divergence,
+/// through recursion; `io` and `state` are reserved for primitives with side effects.
+///
+/// Returns the annotated type of every let-bound name.
+pub fn infer_effects(program: &mut TypedASTExpr, methods: &MethodTable) -> HashMap<NameIdentifier, Ty> {
+    let mut inference = EffectInference { methods, env: HashMap::new(), bindings: HashMap::new() };
+    inference.expr(program);
+    inference.bindings
+}
+
+struct EffectInference<'m> {
+    methods: &'m MethodTable,
+    // Types of the bound variables, with the effects of their arrows
+    env: HashMap<NameIdentifier, Ty>,
+    bindings: HashMap<NameIdentifier, Ty>,
+}
+
+impl EffectInference<'_> {
+    /// Annotate `expr`, returning its annotated type and the effects of evaluating it
+    fn expr(&mut self, expr: &mut TypedASTExpr) -> (Ty, Effect) {
+        match expr {
+            ASTExpr::Atom(ASTAtom::Var(ident), ty, _) => {
+                if let Some(bound) = self.env.get(&ident.id) {
+                    *ty = overlay(ty, bound);
+                } else if self.methods.is_method(&ident.id) {
+                    *ty = overlay(ty, &self.method_ty(&ident.id, ty));
+                }
+
+                (ty.clone(), Effect::PURE)
+            }
+            ASTExpr::Atom(_, ty, _) => (ty.clone(), Effect::PURE),
+            ASTExpr::If { cond, then, else_, ty, .. } => {
+                let (_, cond_effect) = self.expr(cond);
+                let (then_ty, then_effect) = self.expr(then);
+                let (else_ty, else_effect) = self.expr(else_);
+
+                *ty = join(&then_ty, &else_ty);
+                (ty.clone(), cond_effect.union(then_effect).union(else_effect))
+            }
+            ASTExpr::Let { bind: (ident, scheme), value, body, ty, .. } => {
+                let (value_ty, value_effect) = if mentions(value, &ident.id) {
+                    self.recursive(&ident.id, value)
+                } else {
+                    self.expr(value)
+                };
+
+                if let Some(scheme) = scheme {
+                    scheme.ty = overlay(&scheme.ty, &value_ty);
+                }
+                self.bindings.insert(ident.id.clone(), value_ty.clone());
+
+                let outer = self.env.insert(ident.id.clone(), value_ty);
+                let (body_ty, body_effect) = self.expr(body);
+                self.restore(&ident.id, outer);
+
+                *ty = body_ty;
+                (ty.clone(), value_effect.union(body_effect))
+            }
+            ASTExpr::Apply { func, args, ty, .. } => {
+                let (func_ty, func_effect) = self.expr(func);
+                let (args_ty, args_effect) = self.expr(args);
+
+                // The function may call its argument, now or from the closure it returns
+                let passed = args_ty.latent_effect();
+
+                let (ret_ty, call_effect) = match func_ty {
+                    Ty::Arrow(_, ret_ty, effect) => (overlay(ty, &ret_ty), effect),
+                    _ => (ty.clone(), Effect::PURE),
+                };
+
+                *ty = with_effect(&ret_ty, passed);
+                (ty.clone(), func_effect.union(args_effect).union(call_effect).union(passed))
+            }
+            ASTExpr::Lambda { arg: (ident, arg_ty), body, ret_ty, .. } => {
+                let outer = self.env.insert(ident.id.clone(), arg_ty.clone());
+                let (body_ty, body_effect) = self.expr(body);
+                self.restore(&ident.id, outer);
+
+                *ret_ty = body_ty;
+                let ty = Ty::Arrow(Box::new(arg_ty.clone()), Box::new(ret_ty.clone()), body_effect);
+                (ty, Effect::PURE)
+            }
+        }
+    }
+
+    /// A recursive definition: each recursive call may diverge. The effects of the
+    /// definition are assumed for its recursive uses until they no longer change.
+    fn recursive(&mut self, id: &NameIdentifier, value: &mut TypedASTExpr) -> (Ty, Effect) {
+        let seed = with_spine_effect(&value.ty(), Effect::DIVERGE);
+        let mut assumed = seed.clone();
+
+        loop {
+            let outer = self.env.insert(id.clone(), assumed.clone());
+            let (value_ty, value_effect) = self.expr(value);
+            self.restore(id, outer);
+
+            let found = join(&value_ty, &seed);
+            if same_effects(&found, &assumed) {
+                return (value_ty, value_effect);
+            }
+
+            assumed = found;
+        }
+    }
+
+    /// A method may be any of its implementations. Implementations are bound in order around
+    /// the program, one not annotated yet calls back into the method and may diverge.
+    fn method_ty(&self, method: &NameIdentifier, ty: &Ty) -> Ty {
+        self.methods.impls(method).into_iter().fold(ty.clone(), |method_ty, impl_ident| {
+            match self.env.get(&impl_ident.id) {
+                Some(impl_ty) => join(&method_ty, &overlay(ty, impl_ty)),
+                None => with_spine_effect(&method_ty, Effect::DIVERGE),
+            }
+        })
+    }
+
+    fn restore(&mut self, id: &NameIdentifier, outer: Option<Ty>) {
+        match outer {
+            Some(ty) => self.env.insert(id.clone(), ty),
+            None => self.env.remove(id),
+        };
+    }
+}
+
+/// `ty` with the effects of the arrows of `annotated` where both have the same shape.
+/// The arrows of `ty` in place of type variables of `annotated` keep their effects.
+fn overlay(ty: &Ty, annotated: &Ty) -> Ty {
+    match (ty, annotated) {
+        (Ty::Arrow(left, right, _), Ty::Arrow(annotated_left, annotated_right, effect)) => Ty::Arrow(
+            Box::new(overlay(left, annotated_left)),
+            Box::new(overlay(right, annotated_right)),
+            *effect,
+        ),
+        (Ty::App(name, args), Ty::App(annotated_name, annotated_args)) if name == annotated_name => Ty::App(
+            name.clone(),
+            args.iter().zip(annotated_args).map(|(arg, annotated)| overlay(arg, annotated)).collect(),
+        ),
+        (Ty::Forall(vars, body), Ty::Forall(_, annotated_body)) => {
+            Ty::Forall(vars.clone(), Box::new(overlay(body, annotated_body)))
+        }
+        _ => ty.clone(),
+    }
+}
+
+/// `a` with the effects of both `a` and `b` on each arrow
+fn join(a: &Ty, b: &Ty) -> Ty {
+    match (a, b) {
+        (Ty::Arrow(left, right, effect), Ty::Arrow(other_left, other_right, other_effect)) => Ty::Arrow(
+            Box::new(join(left, other_left)),
+            Box::new(join(right, other_right)),
+            effect.union(*other_effect),
+        ),
+        (Ty::App(name, args), Ty::App(other_name, other_args)) if name == other_name => Ty::App(
+            name.clone(),
+            args.iter().zip(other_args).map(|(arg, other)| join(arg, other)).collect(),
+        ),
+        (Ty::Forall(vars, body), Ty::Forall(_, other_body)) => Ty::Forall(vars.clone(), Box::new(join(body, other_body))),
+        _ => a.clone(),
+    }
+}
+
+/// Add `effect` to every arrow of `ty` that is not a parameter
+fn with_effect(ty: &Ty, effect: Effect) -> Ty {
+    match ty {
+        Ty::Arrow(left, right, own) => Ty::Arrow(left.clone(), Box::new(with_effect(right, effect)), own.union(effect)),
+        Ty::App(name, args) => Ty::App(name.clone(), args.iter().map(|arg| with_effect(arg, effect)).collect()),
+        Ty::Forall(vars, body) => Ty::Forall(vars.clone(), Box::new(with_effect(body, effect))),
+        _ => ty.clone(),
+    }
+}
+
+/// Add `effect` to the arrows applied one after the other by a call with every argument
+fn with_spine_effect(ty: &Ty, effect: Effect) -> Ty {
+    match ty {
+        Ty::Arrow(left, right, own) => Ty::Arrow(left.clone(), Box::new(with_spine_effect(right, effect)), own.union(effect)),
+        Ty::Forall(vars, body) => Ty::Forall(vars.clone(), Box::new(with_spine_effect(body, effect))),
+        _ => ty.clone(),
+    }
+}
+
+fn same_effects(a: &Ty, b: &Ty) -> bool {
+    match (a, b) {
+        (Ty::Arrow(left, right, effect), Ty::Arrow(other_left, other_right, other_effect)) => {
+            effect == other_effect && same_effects(left, other_left) && same_effects(right, other_right)
+        }
+        (Ty::App(_, args), Ty::App(_, other_args)) => args.iter().zip(other_args).all(|(a, b)| same_effects(a, b)),
+        (Ty::Forall(_, body), Ty::Forall(_, other_body)) => same_effects(body, other_body),
+        _ => true,
+    }
+}
+
+
+/// Whether `id` occurs in `expr`
+fn mentions(expr: &TypedASTExpr, id: &NameIdentifier) -> bool {
+    match expr {
+        ASTExpr::Atom(ASTAtom::Var(ident), _, _) => ident.id == *id,
+        ASTExpr::Atom(..) => false,
+        ASTExpr::If { cond, then, else_, .. } => mentions(cond, id) || mentions(then, id) || mentions(else_, id),
+        ASTExpr::Let { value, body, .. } => mentions(value, id) || mentions(body, id),
+        ASTExpr::Apply { func, args, .. } => mentions(func, id) || mentions(args, id),
+        ASTExpr::Lambda { body, .. } => mentions(body, id),
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use crate::frontend::ast::{ASTAtom, ASTExpr, Span};
+use crate::frontend::ty::{Scheme, Ty, TypeVar};
+use crate::frontend::tyck::tyck::TypedASTExpr;
+use crate::util::pp::pretty_op;
+
+/// A one-to-one renaming between the type variables of two typed ASTs
+#[derive(Default)]
+struct Renaming {
+    left: HashMap<TypeVar, TypeVar>,
+    right: HashMap<TypeVar, TypeVar>,
+}
+
+impl Renaming {
+    fn same_var(&mut self, a: &TypeVar, b: &TypeVar) -> bool {
+        match (self.left.get(a), self.right.get(b)) {
+            (None, None) => {
+                self.left.insert(a.clone(), b.clone());
+                self.right.insert(b.clone(), a.clone());
+                true
+            }
+            (Some(renamed_a), Some(renamed_b)) => renamed_a == b && renamed_b == a,
+            _ => false,
+        }
+    }
+
+    fn same(&mut self, a: &Ty, b: &Ty) -> bool {
+        match (a, b) {
+            (Ty::Mono(a), Ty::Mono(b)) => self.same_var(a, b),
+            (Ty::Arrow(a1, a2, _), Ty::Arrow(b1, b2, _)) => self.same(a1, b1) && self.same(a2, b2),
+            (Ty::App(n1, args1), Ty::App(n2, args2)) => {
+                n1 == n2 && args1.len() == args2.len()
+                    && args1.iter().zip(args2).all(|(a, b)| self.same(a, b))
+            }
+            (Ty::Forall(v1, a), Ty::Forall(v2, b)) => {
+                v1.len() == v2.len()
+                    && v1.iter().zip(v2).all(|(a, b)| self.same_var(a, b))
+                    && self.same(a, b)
+            }
+            _ => a == b,
+        }
+    }
+
+    fn same_scheme(&mut self, a: &Scheme, b: &Scheme) -> bool {
+        a.vars.len() == b.vars.len()
+            && a.vars.iter().zip(&b.vars).all(|(a, b)| self.same_var(a, b))
+            && a.constraints.len() == b.constraints.len()
+            && a.constraints.iter().zip(&b.constraints)
+                .all(|(a, b)| a.class == b.class && self.same_var(&a.type_var, &b.type_var))
+            && self.same(&a.ty, &b.ty)
+    }
+}
+
+/// Where the two checkers typed the same program differently: the types given to each
+/// node by the bidirectional checker (`expected`) and by the hm checker (`found`) must be
+/// equal up to the names of their type variables
+pub fn disagreements(expected: &TypedASTExpr, found: &TypedASTExpr) -> Vec<String> {
+    let mut comparison = Comparison { renaming: Renaming::default(), report: Vec::new() };
+    comparison.expr(expected, found, Span::default());
+    comparison.report
+}
+
+struct Comparison {
+    renaming: Renaming,
+    report: Vec<String>,
+}
+
+impl Comparison {
+    fn ty(&mut self, what: String, span: Span, expected: &Ty, found: &Ty) {
+        if !self.renaming.same(expected, found) {
+            self.report.push(format!(
+                "{} near {}: the bidirectional checker gives {}, the hm checker gives {}",
+                what, span, expected, found
+            ));
+        }
+    }
+
+    fn expr(&mut self, expected: &TypedASTExpr, found: &TypedASTExpr, span: Span) {
+        match (expected, found) {
+            (ASTExpr::Atom(atom, ty1, _), ASTExpr::Atom(_, ty2, _)) => {
+                let what = match atom {
+                    ASTAtom::Var(ident) => format!("variable {}", ident.name),
+                    ASTAtom::Op(op) => format!("operator ({})", pretty_op(op)),
+                    _ => "literal".to_string(),
+                };
+                self.ty(what, span, ty1, ty2);
+            }
+            (
+                ASTExpr::If { cond: c1, then: t1, else_: e1, ty: ty1, span },
+                ASTExpr::If { cond: c2, then: t2, else_: e2, ty: ty2, .. },
+            ) => {
+                self.ty("if".to_string(), *span, ty1, ty2);
+                self.expr(c1, c2, *span);
+                self.expr(t1, t2, *span);
+                self.expr(e1, e2, *span);
+            }
+            (
+                ASTExpr::Let { bind: (ident, s1), value: v1, body: b1, ty: ty1, span, .. },
+                ASTExpr::Let { bind: (_, s2), value: v2, body: b2, ty: ty2, .. },
+            ) => {
+                match (s1, s2) {
+                    (None, None) => {}
+                    (Some(s1), Some(s2)) if self.renaming.same_scheme(s1, s2) => {}
+                    _ => self.report.push(format!(
+                        "let {} near {}: the bidirectional checker gives {}, the hm checker gives {}",
+                        ident.name, span, describe_scheme(s1), describe_scheme(s2)
+                    )),
+                }
+
+                self.ty(format!("let {}", ident.name), *span, ty1, ty2);
+                self.expr(v1, v2, *span);
+                self.expr(b1, b2, *span);
+            }
+            (
+                ASTExpr::Apply { func: f1, args: a1, ty: ty1, span },
+                ASTExpr::Apply { func: f2, args: a2, ty: ty2, .. },
+            ) => {
+                self.ty("application".to_string(), *span, ty1, ty2);
+                self.expr(f1, f2, *span);
+                self.expr(a1, a2, *span);
+            }
+            (
+                ASTExpr::Lambda { arg: (ident, arg1), body: b1, ret_ty: ret1, span },
+                ASTExpr::Lambda { arg: (_, arg2), body: b2, ret_ty: ret2, .. },
+            ) => {
+                self.ty(format!("parameter {}", ident.name), *span, arg1, arg2);
+                self.ty("function result".to_string(), *span, ret1, ret2);
+                self.expr(b1, b2, *span);
+            }
+            _ => self.report.push(format!("The checkers produced different programs near {}", span)),
+        }
+    }
+}
+
+fn describe_scheme(scheme: &Option<Scheme>) -> String {
+    match scheme {
+        Some(scheme) => scheme.to_string(),
+        None => "a monomorphic binding".to_string(),
+    }
+}
@@ -1,6 +1,6 @@
-use crate::frontend::hm::subst::Subst;
-use crate::frontend::hm::TypeError;
-use crate::frontend::ty::{Ty, TypeVar};
+use std::fmt;
+use std::fmt::Display;
+use crate::frontend::ty::Ty;
 
 #[derive(Debug)]
 #[derive(Clone)]
@@ -8,14 +8,10 @@ pub enum Constraint {
     Eq(Ty, Ty), // Represents an equality constraint between two types
 }
 
-impl Constraint {
-    pub fn apply(&self, var: &TypeVar, ty: &Ty) -> Constraint {
+impl Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Constraint::Eq(t1, t2) => {
-                let t1 = t1.apply(var, ty);
-                let t2 = t2.apply(var, ty);
-                Constraint::Eq(t1, t2)
-            }
+            Constraint::Eq(t1, t2) => write!(f, "{} ~ {}", t1, t2),
         }
     }
 }
@@ -1,199 +1,464 @@
-use std::collections::VecDeque;
-use crate::frontend::ast::{ASTExpr, InputASTExpr};
+use std::collections::{HashMap, VecDeque};
+use crate::frontend::ast::{ASTAtom, ASTExpr, Decl, OpType, Span};
 use crate::frontend::FrontendError;
 use crate::frontend::hm::constraint::Constraint;
-use crate::frontend::hm::subst::Subst;
-use crate::frontend::hm::TypeError;
-pub(crate) use crate::frontend::ty::{MonoContext, Scheme, Ty};
+use crate::frontend::name_resolution::{ResolvedASTExpr, ResolvedIdent, ResolvedProgram, UnboundName};
+use crate::frontend::ty::{Effect, Scheme, Ty, TypeVar, TypingContext};
+use crate::frontend::tyck::common::{Inference, MethodImpl};
+use crate::frontend::tyck::kind::KindEnv;
+use crate::frontend::tyck::subst::apply_subst;
+use crate::frontend::tyck::tyck::{comparison_scheme, unwrap_ast_expr, TypedASTExpr};
+use crate::frontend::tyck::type_class::{ClassEnv, MethodTable, TypeClassConstraint, Wanted};
+use crate::frontend::tyck::union_find::UnionFind;
 
-pub struct FreshVarGenerator {
-    next_id: usize,
+/// Hindley–Milner type checker. Equality constraints are collected while walking
+/// the expression and solved at each let binding, before generalizing it.
+pub struct HMChecker {
+    context: TypingContext,
+    subst: UnionFind,
+    // Equalities not solved yet
+    constraints: Vec<Constraint>,
+    classes: ClassEnv,
+    kinds: KindEnv,
+    // Class constraints arising from uses of constrained bindings
+    wanted: Vec<Wanted>,
+    // Class constraints assumed while checking annotated bindings
+    given: Vec<TypeClassConstraint>,
+    // Skolems of the quantified variables of annotations, and what bound each of them
+    rigid: HashMap<TypeVar, String>,
+    // Type variables of the enclosing annotations, mapped to their skolems
+    scoped: HashMap<TypeVar, TypeVar>,
 }
 
-impl FreshVarGenerator {
-    pub fn new() -> Self {
-        FreshVarGenerator { next_id: 0 }
+impl Default for HMChecker {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    pub fn fresh_var(&mut self) -> Ty {
-        let var_name = format!("t{}", self.next_id);
-        self.next_id += 1;
-        Ty::Mono(var_name)
+impl HMChecker {
+    pub fn new() -> Self {
+        HMChecker {
+            context: TypingContext::new(),
+            subst: UnionFind::new(),
+            constraints: Vec::new(),
+            classes: ClassEnv::new(),
+            kinds: KindEnv::new(),
+            wanted: Vec::new(),
+            given: Vec::new(),
+            rigid: HashMap::new(),
+            scoped: HashMap::new(),
+        }
     }
 
-    pub fn annotate(&mut self, ty: Option<Ty>) -> Ty {
-        ty.unwrap_or_else(|| self.fresh_var())
+    /// Method implementations of all instances, for resolving method calls after monomorphization
+    pub fn method_table(&self) -> &MethodTable {
+        self.classes.methods()
     }
-}
 
-pub fn annotate_type_var(expr: InputASTExpr, fresh_var_generator: &mut FreshVarGenerator) -> Result<ASTExpr<Ty>, FrontendError> {
-    match expr {
-        InputASTExpr::Atom(atom) => {
-            Ok(ASTExpr::Atom(atom))
-        }
-        InputASTExpr::If { cond, then , else_, ty } => {
-            Ok(ASTExpr::If {
-                cond: Box::new(annotate_type_var(*cond, fresh_var_generator)?),
-                then: Box::new(annotate_type_var(*then, fresh_var_generator)?),
-                else_: Box::new(annotate_type_var(*else_, fresh_var_generator)?),
-                ty: fresh_var_generator.annotate(ty),
-            })
-        }
-        InputASTExpr::Let { bind: (bind_name, bind_ty), value, body, ty } => {
-            Ok(ASTExpr::Let {
-                bind: (bind_name, fresh_var_generator.annotate(bind_ty)),
-                value: Box::new(annotate_type_var(*value, fresh_var_generator)?),
-                body: Box::new(annotate_type_var(*body, fresh_var_generator)?),
-                ty: fresh_var_generator.annotate(ty),
-            })
-        }
-        InputASTExpr::Apply { func, args, ty } => {
-            Ok(ASTExpr::Apply {
-                func: Box::new(annotate_type_var(*func, fresh_var_generator)?),
-                args: Box::new(annotate_type_var(*args, fresh_var_generator)?),
-                ty: fresh_var_generator.annotate(ty),
-            })
-        }
-        InputASTExpr::Lambda { arg: (arg_name, arg_ty), body, ret_ty } => {
-            Ok(ASTExpr::Lambda {
-                arg: (arg_name, fresh_var_generator.annotate(arg_ty)),
-                body: Box::new(annotate_type_var(*body, fresh_var_generator)?),
-                ret_ty: fresh_var_generator.annotate(ret_ty),
-            })
+    /// Solve the equality constraints collected so far by unification. The rigid
+    /// variables only unify with themselves and with flexible variables.
+    fn solve(&mut self) -> Result<(), FrontendError> {
+        let mut constraints: VecDeque<Constraint> = std::mem::take(&mut self.constraints).into();
+
+        while let Some(Constraint::Eq(t1, t2)) = constraints.pop_front() {
+            match (self.subst.apply(t1), self.subst.apply(t2)) {
+                (Ty::Mono(var1), Ty::Mono(var2)) if var1 == var2 => {}
+                (Ty::Var(id1), Ty::Var(id2)) if id1 == id2 => {}
+                (var, ty) | (ty, var) if self.is_flexible(&var) => {
+                    self.solve_var(&var, ty)?;
+                }
+                (Ty::Arrow(left1, right1, _), Ty::Arrow(left2, right2, _)) => {
+                    // Unify the left and right parts of the arrows
+                    constraints.push_front(Constraint::Eq(*right1, *right2));
+                    constraints.push_front(Constraint::Eq(*left1, *left2));
+                }
+                (Ty::App(name1, args1), Ty::App(name2, args2)) if name1 == name2 && args1.len() == args2.len() => {
+                    for (arg1, arg2) in args1.into_iter().zip(args2).rev() {
+                        constraints.push_front(Constraint::Eq(arg1, arg2));
+                    }
+                }
+                // Base types
+                (Ty::Int, Ty::Int) | (Ty::Bool, Ty::Bool) | (Ty::Unit, Ty::Unit) | (Ty::String, Ty::String) => {}
+                (Ty::Mono(var), ty) | (ty, Ty::Mono(var)) => {
+                    return Err(FrontendError::TypeError(format!(
+                        "Cannot unify rigid type variable {} with {}",
+                        var, ty
+                    )));
+                }
+                // If types are not unifiable
+                (t1, t2) => {
+                    return Err(FrontendError::TypeError(format!(
+                        "Cannot unify types: {} and {}",
+                        t1, t2
+                    )));
+                }
+            }
         }
+
+        Ok(())
     }
-}
 
-pub fn extract_constraints(expr: &ASTExpr<Ty>, constraints: &mut Vec<Constraint>, gamma: &mut MonoContext) -> Result<Ty, FrontendError> {
-    match expr {
-        ASTExpr::Atom(atom) => {
-            atom.ty(gamma)
-        }
-        ASTExpr::If { cond, then, else_, ty } => {
-            let cond_ty = extract_constraints(cond, constraints, gamma)?;
-            let then_ty = extract_constraints(then, constraints, gamma)?;
-            let else_ty = extract_constraints(else_, constraints, gamma)?;
+    /// The type of a node, a fresh type variable unless it is already known
+    fn annotate(&mut self, ty: Option<Ty>) -> Ty {
+        ty.unwrap_or_else(|| self.fresh_ty())
+    }
 
-            // Ensure that the condition is a boolean
-            constraints.push(Constraint::Eq(cond_ty.clone(), Ty::Bool));
+    /// A rigid type variable standing for the quantified variable `var` of an annotation,
+    /// numbered like the unification variables
+    fn fresh_skolem(&mut self, var: &TypeVar, binder: &str) -> TypeVar {
+        let skolem = format!("{}#{}", var, self.subst.fresh());
+        self.rigid.insert(skolem.clone(), binder.to_string());
+        skolem
+    }
 
-            // `then` and `else` branches must have the same type as the overall type
-            constraints.push(Constraint::Eq(then_ty.clone(), ty.clone()));
-            constraints.push(Constraint::Eq(else_ty.clone(), ty.clone()));
+    fn require(&mut self, t1: Ty, t2: Ty) {
+        self.constraints.push(Constraint::Eq(t1, t2));
+    }
 
-            Ok(ty.clone())
-        }
-        ASTExpr::Let { bind, value, body, ty } => {
-            // Evaluate the value expression to get its type
-            let value_ty = extract_constraints(value, constraints, gamma)?;
+    /// Type the value of a let binding and bring the binding into scope, returning
+    /// the type variables the value abstracts over
+    fn bind_let(
+        &mut self,
+        ident: &ResolvedIdent,
+        scheme: &mut Option<Scheme>,
+        value: &mut ResolvedASTExpr,
+    ) -> Result<Vec<TypeVar>, FrontendError> {
+        match scheme {
+            None => {
+                // The value is typed one let level deeper
+                let wanted_start = self.wanted.len();
+                self.subst.enter_level();
+                let value_ty = self.extract_constraints(value);
+                self.subst.leave_level();
+                let value_ty = value_ty?;
+
+                // The constraints of the value must be solved before generalizing
+                self.solve()?;
+
+                // Only syntactic functions are generalized, so that every
+                // instance can be specialized by monomorphization
+                let inferred = if matches!(*value, ResolvedASTExpr::Lambda { .. }) {
+                    self.generalize(value_ty, wanted_start)
+                } else {
+                    Scheme {
+                        ty: value_ty,
+                        constraints: vec![],
+                        vars: vec![],
+                    }
+                };
 
-            // Bind the variable in the context
-            gamma.push(&bind.0, bind.1.clone());
+                if !inferred.vars.is_empty() {
+                    *scheme = Some(inferred.clone());
+                }
 
-            let body_ty = extract_constraints(body, constraints, gamma)?;
+                let ty_params = inferred.vars.clone();
+                self.context.insert(ident.id.clone(), inferred);
 
-            gamma.pop();
+                Ok(ty_params)
+            }
+            Some(scheme) => {
+                self.annotation(&scheme.ty)?;
+                scheme.ty = self.scoped_ty(scheme.ty.clone(), &scheme.vars);
 
-            // Ensure the bound variable has the correct type
-            constraints.push(Constraint::Eq(value_ty.clone(), bind.1.clone()));
+                // The quantified variables are rigid while typing the definition
+                let binder = format!("the annotation of {}", ident.name);
+                let skolems: Vec<TypeVar> = scheme.vars.iter().map(|var| self.fresh_skolem(var, &binder)).collect();
 
-            // Ensure the body type matches the overall type
-            constraints.push(Constraint::Eq(body_ty.clone(), ty.clone()));
+                let skolem_of: HashMap<TypeVar, TypeVar> = self.scoped.clone().into_iter()
+                    .chain(scheme.vars.iter().cloned().zip(skolems.iter().cloned()))
+                    .collect();
+                let to_skolems: HashMap<TypeVar, Ty> = scheme.vars.iter().cloned()
+                    .zip(skolems.iter().map(|skolem| Ty::Mono(skolem.clone())))
+                    .collect();
+                let skolem_ty = apply_subst(scheme.ty.clone(), &to_skolems);
 
-            Ok(ty.clone())
-        }
-        ASTExpr::Apply { func, args, ty } => {
-            let func_ty = extract_constraints(func, constraints, gamma)?;
-            let args_ty = extract_constraints(args, constraints, gamma)?;
+                // Assumed in the definition only
+                let outer_given = self.given.clone();
+                let wanted_start = self.wanted.len();
+                self.given.extend(scheme.constraints.iter().map(|constraint| TypeClassConstraint {
+                    class: constraint.class.clone(),
+                    type_var: skolem_of.get(&constraint.type_var)
+                        .cloned()
+                        .unwrap_or_else(|| constraint.type_var.clone()),
+                }));
 
-            // func_ty = arg_ty -> ty
-            constraints.push(Constraint::Eq(
-                func_ty.clone(),
-                Ty::Arrow(Box::new(args_ty.clone()), Box::new(ty.clone())),
-            ));
+                // In scope in its own definition, for recursion
+                self.context.insert(ident.id.clone(), scheme.clone());
 
-            Ok(ty.clone())
-        }
-        ASTExpr::Lambda { arg: (arg_name, arg_ty), body, ret_ty } => {
-            // Bind the argument type in the context
-            gamma.push(arg_name, arg_ty.clone());
+                let outer_scope = self.scoped.clone();
+                self.scoped.extend(scheme.vars.iter().cloned().zip(skolems.iter().cloned()));
+
+                let value_ty = self.extract_constraints(value);
+                self.scoped = outer_scope;
 
-            let body_ty = extract_constraints(body, constraints, gamma)?;
+                self.require(skolem_ty, value_ty?);
+                self.solve()?;
 
-            gamma.pop();
+                self.discharge_given(wanted_start);
+                self.given = outer_given;
 
-            // Ensure the body type matches the return type
-            constraints.push(Constraint::Eq(body_ty.clone(), ret_ty.clone()));
+                // The definition must be as polymorphic as its annotation
+                self.check_escape(&skolems, &scheme.ty)?;
 
-            // Return the function type
-            Ok(Ty::Arrow(Box::new(arg_ty.clone()), Box::new(ret_ty.clone())))
+                Ok(skolems)
+            }
         }
     }
-}
 
-pub fn unify(constraints: &[Constraint]) -> Result<Subst, FrontendError> {
-    pub fn unify_impl(constraints: &mut VecDeque<Constraint>, subst: &mut Subst) -> Result<(), TypeError> {
-        println!("Unifying constraints: {:?}", constraints);
-        println!("Current substitution: {:?}", subst);
-        
-        let constraint = match constraints.pop_front() {
-            Some(c) => c,
-            None => return Ok(()), // No more constraints to process
-        };
-        
-        match constraint {
-            Constraint::Eq(t1, t2) => {
-                let t1 = subst.apply(t1.clone());
-                let t2 = subst.apply(t2.clone());
-
-                match (t1, t2) {
-                    (Ty::Mono(var1), Ty::Mono(var2)) if var1 == var2 => {
-                        // Solve the rest recursively
-                        unify_impl(constraints, subst)
-                    },
-                    (Ty::Mono(var), ty) | (ty, Ty::Mono(var)) => {
-                        // Occurrence check
-                        if ty.contains_var(&var) {
-                            return Err(TypeError::UnificationError(format!(
-                                "Cannot unify type variable {} with type {} due to occurrence check",
-                                var, ty
-                            )));
-                        }
-                        
-                        // Apply the new-found substitution on the remaining constraints
-                        for constraint in constraints.iter_mut() {
-                            *constraint = constraint.apply(&var, &ty);
-                        }
+    /// Annotate every node of `expr` with its type, possibly a type variable,
+    /// and collect the equalities between these types
+    pub fn extract_constraints(&mut self, expr: &mut ResolvedASTExpr) -> Result<Ty, FrontendError> {
+        match expr {
+            ASTExpr::Atom(atom, atom_ty, atom_ty_args) => {
+                let ty = match atom {
+                    ASTAtom::Int(_) => Ty::Int,
+                    ASTAtom::Bool(_) => Ty::Bool,
+                    ASTAtom::Str(_) => Ty::String,
+                    ASTAtom::Unit => Ty::Unit,
+                    ASTAtom::Var(ident) => {
+                        let scheme = self.context.get(&ident.id).cloned().ok_or_else(|| {
+                            FrontendError::UnboundVariable(UnboundName::new(ident.name.clone()))
+                        })?;
 
-                        // Solve the rest recursively
-                        unify_impl(constraints, subst)?;
-                        
-                        // Extend the substitution
-                        subst.extend(var, ty)
+                        let (ty, constraints, ty_args) = self.instantiate(&scheme);
+                        self.wanted.extend(constraints);
+                        *atom_ty_args = ty_args;
+                        ty
+                    }
+                    ASTAtom::Op(op) => match op {
+                        OpType::Add | OpType::Sub | OpType::Mul | OpType::Div => Ty::Arrow(
+                            Box::new(Ty::Int),
+                            Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int), Effect::PURE)),
+                            Effect::PURE,
+                        ),
+                        _ => {
+                            let (ty, constraints, _) = self.instantiate(&comparison_scheme(op));
+                            self.wanted.extend(constraints);
+                            ty
+                        }
                     },
-                    (Ty::Arrow(left1, right1), Ty::Arrow(left2, right2)) => {
-                        // Unify the left and right parts of the arrows
-                        constraints.push_front(Constraint::Eq(*left1, *left2));
-                        constraints.push_front(Constraint::Eq(*right1, *right2));
-                        unify_impl(constraints, subst)
+                    ASTAtom::Hole(..) => {
+                        return Err(FrontendError::TypeError(
+                            "Typed holes are not supported by the hm checker, use --checker=bidirectional".to_string()
+                        ));
+                    }
+                };
+
+                *atom_ty = Some(ty.clone());
+                Ok(ty)
+            }
+            ASTExpr::If { cond, then, else_, ty, .. } => {
+                let cond_ty = self.extract_constraints(cond)?;
+                let then_ty = self.extract_constraints(then)?;
+                let else_ty = self.extract_constraints(else_)?;
+
+                // Ensure that the condition is a boolean
+                self.require(cond_ty, Ty::Bool);
+
+                // `then` and `else` branches must have the same type as the overall type
+                let if_ty = self.annotate(ty.take());
+                self.require(then_ty, if_ty.clone());
+                self.require(else_ty, if_ty.clone());
+
+                *ty = Some(if_ty.clone());
+                Ok(if_ty)
+            }
+            ASTExpr::Let { bind: (ident, scheme), value, body, ty, ty_params, .. } => {
+                *ty_params = self.bind_let(ident, scheme, value)?;
+
+                let body_ty = self.extract_constraints(body)?;
+
+                // The binding goes out of scope
+                self.context.remove(&ident.id);
+
+                *ty = Some(body_ty.clone());
+                Ok(body_ty)
+            }
+            ASTExpr::Apply { func, args, ty, .. } => {
+                let func_ty = self.extract_constraints(func)?;
+                let args_ty = self.extract_constraints(args)?;
+
+                // func_ty = arg_ty -> ty
+                let ret_ty = self.annotate(ty.take());
+                self.require(func_ty, Ty::Arrow(Box::new(args_ty), Box::new(ret_ty.clone()), Effect::PURE));
+
+                *ty = Some(ret_ty.clone());
+                Ok(ret_ty)
+            }
+            ASTExpr::Lambda { arg: (ident, arg_ty), body, ret_ty, .. } => {
+                let param_ty = match arg_ty.take() {
+                    Some(annotated) => {
+                        self.annotation(&annotated)?;
+                        self.scoped_ty(annotated, &[])
+                    }
+                    None => self.fresh_ty(),
+                };
+                *arg_ty = Some(param_ty.clone());
+
+                // Bind the argument type in the context
+                self.context.insert(ident.id.clone(), Scheme {
+                    ty: param_ty.clone(),
+                    constraints: vec![],
+                    vars: vec![],
+                });
+
+                let body_ty = self.extract_constraints(body)?;
+
+                self.context.remove(&ident.id);
+
+                *ret_ty = Some(body_ty.clone());
+                Ok(Ty::Arrow(Box::new(param_ty), Box::new(body_ty), Effect::PURE))
+            }
+        }
+    }
+
+    /// Replace the type variables of every annotation by their solutions, naming
+    /// the unsolved ones
+    fn final_apply(&self, expr: &mut ResolvedASTExpr) {
+        let resolve = |ty: &mut Option<Ty>| *ty = ty.take().map(|ty| self.subst.apply_named(ty));
+
+        match expr {
+            ASTExpr::Atom(_, ty, ty_args) => {
+                resolve(ty);
+                for ty_arg in ty_args {
+                    *ty_arg = self.subst.apply_named(ty_arg.clone());
+                }
+            }
+            ASTExpr::If { cond, then, else_, ty, .. } => {
+                resolve(ty);
+                self.final_apply(cond);
+                self.final_apply(then);
+                self.final_apply(else_);
+            }
+            ASTExpr::Let { value, body, ty, .. } => {
+                resolve(ty);
+                self.final_apply(value);
+                self.final_apply(body);
+            }
+            ASTExpr::Apply { func, args, ty, .. } => {
+                resolve(ty);
+                self.final_apply(func);
+                self.final_apply(args);
+            }
+            ASTExpr::Lambda { arg: (_, arg_ty), body, ret_ty, .. } => {
+                resolve(arg_ty);
+                resolve(ret_ty);
+                self.final_apply(body);
+            }
+        }
+    }
+
+    pub fn tyck(&mut self, program: ResolvedProgram) -> Result<TypedASTExpr, FrontendError> {
+        let mut impls = Vec::new();
+
+        for decl in program.decls {
+            match decl {
+                Decl::Type(decl) => self.kinds.declare(&decl)?,
+                Decl::Class(class) => self.declare_class(class)?,
+                Decl::Instance(instance) => {
+                    for MethodImpl { ident, mut value, expected, origin } in self.declare_instance(instance)? {
+                        let checked = self.extract_constraints(&mut value).and_then(|value_ty| {
+                            self.require(expected, value_ty);
+                            self.solve()
+                        });
+
+                        // Say which method the error is in
+                        checked.map_err(|error| match error {
+                            FrontendError::TypeError(message) => {
+                                FrontendError::TypeError(format!("In {}: {}", origin, message))
+                            }
+                            error => error,
+                        })?;
+
+                        impls.push((ident, value));
                     }
-                    // Base types
-                    (Ty::Int, Ty::Int) | (Ty::Bool, Ty::Bool) | (Ty::Unit, Ty::Unit) => Ok(()),
-                    // If types are not unifiable
-                    (t1, t2) => Err(TypeError::UnificationError(format!(
-                        "Cannot unify types: {} and {}",
-                        t1, t2
-                    ))),
                 }
             }
         }
+
+        let mut ast = program.body;
+
+        let ty = self.extract_constraints(&mut ast)?;
+        self.solve()?;
+
+        // All class constraints must be resolved by now
+        self.solve_classes()?;
+
+        self.final_apply(&mut ast);
+        let mut typed_ast = unwrap_ast_expr(ast);
+
+        // Instance methods are bound around the program, as by the bidirectional checker
+        let ty = self.subst.apply_named(ty);
+        for (impl_ident, mut value) in impls.into_iter().rev() {
+            self.final_apply(&mut value);
+
+            typed_ast = ASTExpr::Let {
+                bind: (impl_ident, None),
+                value: Box::new(unwrap_ast_expr(value)),
+                body: Box::new(typed_ast),
+                ty: ty.clone(),
+                ty_params: vec![],
+                span: Span::default(),
+            };
+        }
+
+        Ok(typed_ast)
+    }
+}
+
+impl Inference for HMChecker {
+    fn context(&self) -> &TypingContext {
+        &self.context
+    }
+
+    fn classes(&self) -> &ClassEnv {
+        &self.classes
+    }
+
+    fn classes_mut(&mut self) -> &mut ClassEnv {
+        &mut self.classes
+    }
+
+    fn subst(&self) -> &UnionFind {
+        &self.subst
+    }
+
+    fn subst_mut(&mut self) -> &mut UnionFind {
+        &mut self.subst
     }
 
-    let mut subst = Subst::empty();
-    let mut constraints_queue: VecDeque<Constraint> = constraints.iter().cloned().collect();
-    unify_impl(&mut constraints_queue, &mut subst).or_else(|err| {
-        Err(FrontendError::TypeError(err))
-    })?;
+    fn wanted(&mut self) -> &mut Vec<Wanted> {
+        &mut self.wanted
+    }
+
+    fn given(&self) -> &[TypeClassConstraint] {
+        &self.given
+    }
+
+    fn scoped(&self) -> &HashMap<TypeVar, TypeVar> {
+        &self.scoped
+    }
+
+    fn rigid(&self) -> &HashMap<TypeVar, String> {
+        &self.rigid
+    }
 
-    Ok(subst)
-}
\ No newline at end of file
+    /// Only the bidirectional checker knows polymorphic types inside other types
+    fn annotation(&self, ty: &Ty) -> Result<(), FrontendError> {
+        self.kinds.check(ty)?;
+
+        if ty.is_higher_rank() {
+            return Err(FrontendError::TypeError(format!(
+                "Higher-rank type {} is not supported by the hm checker, use --checker=bidirectional",
+                ty
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn bind_var(&mut self, ident: &ResolvedIdent, scheme: Scheme) {
+        self.context.insert(ident.id.clone(), scheme);
+    }
+}
@@ -1,10 +1,7 @@
+//! Constraint-based Hindley–Milner type inference: the whole expression is
+//! annotated with type variables, equality constraints are extracted from it and
+//! solved by unification. It is an alternative to the bidirectional checker in
+//! `tyck`, without higher-rank types, and produces the same typed AST.
 pub mod infer;
 pub mod constraint;
-pub mod subst;
-
-#[derive(Debug)]
-pub enum TypeError {
-    UnificationError(String), // Represents an error during type unification
-    ConstraintError(String),  // Represents an error in constraints
-    Other(String),            // Represents any other type error
-}
\ No newline at end of file
+pub mod compare;
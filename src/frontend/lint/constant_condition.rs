@@ -0,0 +1,80 @@
+use crate::frontend::ast::{ASTAtom, OpType};
+use crate::frontend::lint::{walk, Lint, Warning};
+use crate::frontend::name_resolution::ResolvedASTExpr;
+
+pub const NAME: &str = "constant-condition";
+
+/// An `if` whose condition is made of literals only, so that one branch is dead
+pub struct ConstantCondition;
+
+impl Lint for ConstantCondition {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn check(&self, expr: &ResolvedASTExpr, warnings: &mut Vec<Warning>) {
+        walk(expr, &mut |expr| {
+            if let ResolvedASTExpr::If { cond, span, .. } = expr
+                && let Some(Constant::Bool(value)) = evaluate(cond)
+            {
+                let dead = if value { "else" } else { "then" };
+                warnings.push(Warning {
+                    lint: NAME,
+                    span: *span,
+                    message: format!("the condition is always {}, the {} branch is never taken", value, dead),
+                });
+            }
+        });
+    }
+}
+
+#[derive(PartialEq, PartialOrd)]
+enum Constant {
+    Int(i32),
+    Bool(bool),
+    Str(String),
+}
+
+/// The value of an expression of literals and operators, if it cannot fail
+fn evaluate(expr: &ResolvedASTExpr) -> Option<Constant> {
+    match expr {
+        ResolvedASTExpr::Atom(ASTAtom::Int(value), _, _) => Some(Constant::Int(*value)),
+        ResolvedASTExpr::Atom(ASTAtom::Bool(value), _, _) => Some(Constant::Bool(*value)),
+        // Escape sequences are kept as written, equal strings may be spelled differently
+        ResolvedASTExpr::Atom(ASTAtom::Str(value), _, _) if !value.contains('\\') => Some(Constant::Str(value.clone())),
+        ResolvedASTExpr::Apply { func, args: right, .. } => {
+            let ResolvedASTExpr::Apply { func: op, args: left, .. } = &**func else {
+                return None;
+            };
+            let ResolvedASTExpr::Atom(ASTAtom::Op(op), _, _) = &**op else {
+                return None;
+            };
+
+            binary(op, evaluate(left)?, evaluate(right)?)
+        }
+        _ => None,
+    }
+}
+
+fn binary(op: &OpType, left: Constant, right: Constant) -> Option<Constant> {
+    use Constant::*;
+
+    // Operands of different types are a type error, reported by the type checker
+    if std::mem::discriminant(&left) != std::mem::discriminant(&right) {
+        return None;
+    }
+
+    match (op, left, right) {
+        (OpType::Add, Int(l), Int(r)) => l.checked_add(r).map(Int),
+        (OpType::Sub, Int(l), Int(r)) => l.checked_sub(r).map(Int),
+        (OpType::Mul, Int(l), Int(r)) => l.checked_mul(r).map(Int),
+        (OpType::Div, Int(l), Int(r)) => l.checked_div(r).map(Int),
+        (OpType::Eq, l, r) => Some(Bool(l == r)),
+        (OpType::Neq, l, r) => Some(Bool(l != r)),
+        (OpType::Lt, l, r) => l.partial_cmp(&r).map(|order| Bool(order.is_lt())),
+        (OpType::Gt, l, r) => l.partial_cmp(&r).map(|order| Bool(order.is_gt())),
+        (OpType::Leq, l, r) => l.partial_cmp(&r).map(|order| Bool(order.is_le())),
+        (OpType::Geq, l, r) => l.partial_cmp(&r).map(|order| Bool(order.is_ge())),
+        _ => None,
+    }
+}
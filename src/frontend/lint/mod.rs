@@ -0,0 +1,117 @@
+use std::fmt;
+use std::fmt::Display;
+use crate::frontend::ast::{ASTAtom, Decl, Span};
+use crate::frontend::name_resolution::{NameIdentifier, ResolvedASTExpr, ResolvedProgram};
+
+mod constant_condition;
+mod recursion;
+mod shadowing;
+mod unused;
+
+/// A suspicious piece of code found by a lint. Warnings never stop compilation.
+#[derive(Debug)]
+pub struct Warning {
+    pub lint: &'static str,
+    pub span: Span,
+    pub message: String,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "warning[{}] at {}: {}", self.lint, self.span, self.message)
+    }
+}
+
+/// A check over the resolved program: identifiers are unique, so bindings can be
+/// told apart from the names they shadow
+pub trait Lint {
+    /// The name the lint is enabled or disabled with on the command line
+    fn name(&self) -> &'static str;
+
+    /// Whether the lint runs unless it is disabled
+    fn enabled_by_default(&self) -> bool {
+        true
+    }
+
+    fn check(&self, expr: &ResolvedASTExpr, warnings: &mut Vec<Warning>);
+}
+
+/// Names of all lints, in the order they run
+pub const LINTS: [&str; 4] = [
+    unused::NAME,
+    shadowing::NAME,
+    constant_condition::NAME,
+    recursion::NAME,
+];
+
+fn all() -> Vec<Box<dyn Lint>> {
+    vec![
+        Box::new(unused::UnusedVariable),
+        Box::new(shadowing::Shadowing),
+        Box::new(constant_condition::ConstantCondition),
+        Box::new(recursion::UnconditionalRecursion),
+    ]
+}
+
+/// Run the default lints, except those in `allow`, and those in `warn`, over the program
+/// and the method implementations of its instances
+pub fn run(program: &ResolvedProgram, allow: &[String], warn: &[String]) -> Vec<Warning> {
+    let exprs: Vec<&ResolvedASTExpr> = program.decls.iter()
+        .filter_map(|decl| match decl {
+            Decl::Instance(instance) => Some(instance.methods.iter().map(|(_, value)| value)),
+            _ => None,
+        })
+        .flatten()
+        .chain(std::iter::once(&program.body))
+        .collect();
+
+    let mut warnings = Vec::new();
+
+    for lint in all() {
+        let name = lint.name().to_string();
+        let enabled = warn.contains(&name) || (lint.enabled_by_default() && !allow.contains(&name));
+
+        if enabled {
+            for expr in &exprs {
+                lint.check(expr, &mut warnings);
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Call `visit` on `expr` and each of its subexpressions, parents first
+fn walk<'e>(expr: &'e ResolvedASTExpr, visit: &mut impl FnMut(&'e ResolvedASTExpr)) {
+    visit(expr);
+
+    match expr {
+        ResolvedASTExpr::Atom(..) => {}
+        ResolvedASTExpr::If { cond, then, else_, .. } => {
+            walk(cond, visit);
+            walk(then, visit);
+            walk(else_, visit);
+        }
+        ResolvedASTExpr::Let { value, body, .. } => {
+            walk(value, visit);
+            walk(body, visit);
+        }
+        ResolvedASTExpr::Apply { func, args, .. } => {
+            walk(func, visit);
+            walk(args, visit);
+        }
+        ResolvedASTExpr::Lambda { body, .. } => walk(body, visit),
+    }
+}
+
+/// Whether `id` occurs in `expr`
+fn mentions(expr: &ResolvedASTExpr, id: &NameIdentifier) -> bool {
+    let mut found = false;
+    walk(expr, &mut |expr| {
+        if let ResolvedASTExpr::Atom(ASTAtom::Var(ident), _, _) = expr {
+            found |= ident.id == *id;
+        }
+    });
+
+    found
+}
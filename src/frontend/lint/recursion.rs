@@ -0,0 +1,68 @@
+use crate::frontend::ast::ASTAtom;
+use crate::frontend::lint::{mentions, walk, Lint, Warning};
+use crate::frontend::name_resolution::{NameIdentifier, ResolvedASTExpr};
+
+pub const NAME: &str = "unconditional-recursion";
+
+/// A recursive binding without a base case: every evaluation of its body calls it again
+pub struct UnconditionalRecursion;
+
+impl Lint for UnconditionalRecursion {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn check(&self, expr: &ResolvedASTExpr, warnings: &mut Vec<Warning>) {
+        walk(expr, &mut |expr| {
+            // Only an annotated binding is in scope in its value
+            let ResolvedASTExpr::Let { bind: (ident, Some(_)), value, span, .. } = expr else {
+                return;
+            };
+
+            if !mentions(value, &ident.id) {
+                return;
+            }
+
+            // The body runs once every parameter is applied
+            let mut body = &**value;
+            let mut arity = 0;
+            while let ResolvedASTExpr::Lambda { body: inner, .. } = body {
+                body = inner;
+                arity += 1;
+            }
+
+            if always_calls(body, &ident.id, arity) {
+                warnings.push(Warning {
+                    lint: NAME,
+                    span: *span,
+                    message: format!("{} calls itself on every path, it has no base case and never returns", ident.name),
+                });
+            }
+        });
+    }
+}
+
+/// Whether evaluating `expr` always applies `id` to at least `arity` arguments
+fn always_calls(expr: &ResolvedASTExpr, id: &NameIdentifier, arity: usize) -> bool {
+    match expr {
+        ResolvedASTExpr::Atom(ASTAtom::Var(ident), _, _) => ident.id == *id && arity == 0,
+        ResolvedASTExpr::Atom(..) => false,
+        ResolvedASTExpr::If { cond, then, else_, .. } => {
+            always_calls(cond, id, arity) || (always_calls(then, id, arity) && always_calls(else_, id, arity))
+        }
+        ResolvedASTExpr::Let { value, body, .. } => always_calls(value, id, arity) || always_calls(body, id, arity),
+        ResolvedASTExpr::Apply { func, args, .. } => {
+            let mut head = &**func;
+            let mut applied = 1;
+            while let ResolvedASTExpr::Apply { func, .. } = head {
+                head = func;
+                applied += 1;
+            }
+
+            let calls = matches!(head, ResolvedASTExpr::Atom(ASTAtom::Var(ident), _, _) if ident.id == *id);
+            (calls && applied >= arity) || always_calls(func, id, arity) || always_calls(args, id, arity)
+        }
+        // The body of a function is not evaluated until it is called
+        ResolvedASTExpr::Lambda { .. } => false,
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use crate::frontend::ast::Span;
+use crate::frontend::lint::{Lint, Warning};
+use crate::frontend::name_resolution::ResolvedASTExpr;
+
+pub const NAME: &str = "shadowing";
+
+/// A `let` binding or parameter reusing the name of a binding in scope, which
+/// becomes unreachable in its body
+pub struct Shadowing;
+
+impl Lint for Shadowing {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn check(&self, expr: &ResolvedASTExpr, warnings: &mut Vec<Warning>) {
+        check_scope(expr, &mut HashMap::new(), warnings);
+    }
+}
+
+/// `scope` maps the names bound around `expr` to where they are bound
+fn check_scope(expr: &ResolvedASTExpr, scope: &mut HashMap<String, Span>, warnings: &mut Vec<Warning>) {
+    let (ident, span, value, recursive, body) = match expr {
+        ResolvedASTExpr::Atom(..) => return,
+        ResolvedASTExpr::If { cond, then, else_, .. } => {
+            check_scope(cond, scope, warnings);
+            check_scope(then, scope, warnings);
+            check_scope(else_, scope, warnings);
+            return;
+        }
+        ResolvedASTExpr::Apply { func, args, .. } => {
+            check_scope(func, scope, warnings);
+            check_scope(args, scope, warnings);
+            return;
+        }
+        ResolvedASTExpr::Let { bind: (ident, scheme), value, body, span, .. } => {
+            (ident, span, Some(value), scheme.is_some(), body)
+        }
+        ResolvedASTExpr::Lambda { arg: (ident, _), body, span, .. } => (ident, span, None, false, body),
+    };
+
+    // Only the value of an annotated let is in the scope of its binding
+    if let Some(value) = value && !recursive {
+        check_scope(value, scope, warnings);
+    }
+
+    if let Some(shadowed) = scope.get(&ident.name) {
+        warnings.push(Warning {
+            lint: NAME,
+            span: *span,
+            message: format!("{} shadows the binding at {}", ident.name, shadowed),
+        });
+    }
+
+    let outer = scope.insert(ident.name.clone(), *span);
+    if let Some(value) = value && recursive {
+        check_scope(value, scope, warnings);
+    }
+    check_scope(body, scope, warnings);
+
+    match outer {
+        Some(span) => scope.insert(ident.name.clone(), span),
+        None => scope.remove(&ident.name),
+    };
+}
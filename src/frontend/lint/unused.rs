@@ -0,0 +1,32 @@
+use crate::frontend::lint::{mentions, walk, Lint, Warning};
+use crate::frontend::name_resolution::ResolvedASTExpr;
+
+pub const NAME: &str = "unused-variable";
+
+/// A `let` binding or parameter that is never used. Uses in the definition of a
+/// recursive binding do not count. Names starting with `_` are exempt.
+pub struct UnusedVariable;
+
+impl Lint for UnusedVariable {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn check(&self, expr: &ResolvedASTExpr, warnings: &mut Vec<Warning>) {
+        walk(expr, &mut |expr| {
+            let (ident, scope, what, span) = match expr {
+                ResolvedASTExpr::Let { bind: (ident, _), body, span, .. } => (ident, body, "variable", span),
+                ResolvedASTExpr::Lambda { arg: (ident, _), body, span, .. } => (ident, body, "parameter", span),
+                _ => return,
+            };
+
+            if !ident.name.starts_with('_') && !mentions(scope, &ident.id) {
+                warnings.push(Warning {
+                    lint: NAME,
+                    span: *span,
+                    message: format!("unused {} {}, prefix it with an underscore if this is intended", what, ident.name),
+                });
+            }
+        });
+    }
+}
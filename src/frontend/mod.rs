@@ -1,9 +1,16 @@
-use lalrpop_util::lalrpop_mod;
+use std::fmt;
+use lalrpop_util::{lalrpop_mod, ParseError};
+use ast::Span;
 
 #[macro_use] pub(super) mod ast;
 pub mod ty;
-// pub(crate) mod hm;
+pub mod desugar;
+pub mod effect;
+pub mod hm;
+pub mod lint;
 pub mod name_resolution;
+pub mod signatures;
+pub mod surface;
 pub mod tyck;
 
 lalrpop_mod!(mujicalang, "/frontend/mujicalang.rs");
@@ -13,9 +20,67 @@ lalrpop_mod!(mujicalang, "/frontend/mujicalang.rs");
 pub enum FrontendError {
     TypeError(String),
     ParseError(String),
-    UnboundVariable(String),
+    UnboundVariable(name_resolution::UnboundName),
+    // Report of the typed holes in a program, followed by the type error met after them if any
+    TypedHoles(String),
 }
 
-pub fn parse(input: &str) -> ast::InputASTExpr {
-    mujicalang::ExprParser::new().parse(input).unwrap()
-}
\ No newline at end of file
+impl fmt::Display for FrontendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrontendError::TypeError(message) | FrontendError::ParseError(message) => write!(f, "{}", message),
+            FrontendError::UnboundVariable(unbound) => write!(f, "{}", unbound),
+            FrontendError::TypedHoles(report) => write!(f, "{}", report),
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<surface::SurfaceProgram, FrontendError> {
+    mujicalang::ProgramParser::new().parse(input, input).map_err(|error| {
+        let expected = |expected: Vec<String>| {
+            let expected: Vec<String> = expected.iter().map(|terminal| describe_terminal(terminal)).collect();
+
+            match expected.as_slice() {
+                [] => String::new(),
+                [one] => format!(", expected {}", one),
+                many => format!(", expected one of {}", many.join(", ")),
+            }
+        };
+
+        FrontendError::ParseError(match error {
+            ParseError::InvalidToken { location } => {
+                format!("Invalid token at {}", Span::new(input, location))
+            }
+            ParseError::UnrecognizedEof { location, expected: tokens } => {
+                format!("Unexpected end of input at {}{}", Span::new(input, location), expected(tokens))
+            }
+            ParseError::UnrecognizedToken { token: (start, token, _), expected: tokens } => {
+                format!("Unexpected `{}` at {}{}", token, Span::new(input, start), expected(tokens))
+            }
+            ParseError::ExtraToken { token: (start, token, _) } => {
+                format!("Unexpected `{}` at {}", token, Span::new(input, start))
+            }
+            ParseError::User { error } => error.to_string(),
+        })
+    })
+}
+
+/// Names the terminals matched by a regular expression in the grammar, others are quoted tokens
+fn describe_terminal(terminal: &str) -> String {
+    if !terminal.starts_with("r#") {
+        return terminal.to_string();
+    }
+
+    let name = if terminal.contains(r"\\?") {
+        "a hole"
+    } else if terminal.contains("[a-z_]") {
+        "an identifier"
+    } else if terminal.contains("[A-Z]") {
+        "a capitalized name"
+    } else if terminal.contains("[0-9]") {
+        "an integer"
+    } else {
+        "a string"
+    };
+    name.to_string()
+}
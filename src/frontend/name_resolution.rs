@@ -1,9 +1,12 @@
 use std::collections::HashMap;
-use crate::frontend::ast::{ASTAtom, ASTExpr, InputASTExpr};
+use std::fmt;
+use std::fmt::Display;
+use crate::frontend::ast::{ASTAtom, ASTExpr, ClassDecl, Decl, InputASTExpr, InputProgram, InstanceDecl, Program, Span};
 use crate::frontend::FrontendError;
 use crate::frontend::ty::Ty;
 
 pub type ResolvedASTExpr = ASTExpr<ResolvedIdent, Option<Ty>>;
+pub type ResolvedProgram = Program<ResolvedIdent, Option<Ty>>;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct NameIdentifier(pub String);
@@ -40,15 +43,86 @@ impl PartialEq<Self> for ResolvedIdent {
 
 impl Eq for ResolvedIdent {}
 
+/// A variable that is not bound where it is used, with what the resolver knows about
+/// the names it may stand for
+#[derive(Debug)]
+pub struct UnboundName {
+    pub name: String,
+    /// Visible names close to `name`, closest first
+    pub suggestions: Vec<String>,
+    /// Where `name` is bound, if it is bound elsewhere in the program
+    pub elsewhere: Option<Elsewhere>,
+}
+
+#[derive(Debug)]
+pub enum Elsewhere {
+    /// By a binding the use comes before
+    Later(Span),
+    /// In a scope that was closed before the use
+    Sibling(Span),
+    /// By the unannotated let whose value the use is in, which cannot refer to itself
+    Unannotated(Span),
+}
+
+impl UnboundName {
+    pub fn new(name: String) -> Self {
+        UnboundName { name, suggestions: Vec::new(), elsewhere: None }
+    }
+}
+
+impl Display for UnboundName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unbound variable {}", self.name)?;
+
+        match &self.elsewhere {
+            Some(Elsewhere::Later(span)) => {
+                write!(f, "\n  note: {} is bound later, at {}, and is not visible before", self.name, span)?
+            }
+            Some(Elsewhere::Sibling(span)) => {
+                write!(f, "\n  note: {} is bound at {}, in a scope that does not enclose this use", self.name, span)?
+            }
+            Some(Elsewhere::Unannotated(span)) => write!(
+                f,
+                "\n  note: {} is defined at {}, only a binding with a type annotation can refer to itself",
+                self.name, span
+            )?,
+            None => {}
+        }
+
+        match self.suggestions.as_slice() {
+            [] => Ok(()),
+            [name] => write!(f, "\n  help: did you mean {}?", name),
+            names => write!(f, "\n  help: did you mean one of {}?", names.join(", ")),
+        }
+    }
+}
+
 pub struct NameResolver {
     counter: usize,
     scopes: Vec<HashMap<String, ResolvedIdent>>,
+    // Where each binding of the program is, in the order they are resolved
+    binders: Vec<(String, Span)>,
+    // How many of `binders` have been resolved
+    resolved: usize,
+    // Bindings of the scopes already closed
+    closed: HashMap<String, Span>,
+    // Unannotated lets whose value is being resolved
+    defining: Vec<(String, Span)>,
+    spans: HashMap<NameIdentifier, Span>,
 }
 
 impl NameResolver {
     pub fn new() -> Self {
         // With an empty global scope
-        NameResolver { counter: 0, scopes: vec![HashMap::new()] }
+        NameResolver {
+            counter: 0,
+            scopes: vec![HashMap::new()],
+            binders: Vec::new(),
+            resolved: 0,
+            closed: HashMap::new(),
+            defining: Vec::new(),
+            spans: HashMap::new(),
+        }
     }
 
     fn push_scope(&mut self) {
@@ -56,7 +130,47 @@ impl NameResolver {
     }
 
     fn pop_scope(&mut self) {
-        self.scopes.pop();
+        if let Some(scope) = self.scopes.pop() {
+            for ident in scope.values() {
+                if let Some(span) = self.spans.get(&ident.id) {
+                    self.closed.insert(ident.name.clone(), *span);
+                }
+            }
+        }
+    }
+
+    /// Bind `name` in the current scope, at `span` in the source
+    fn bind(&mut self, name: String, span: Span) -> ResolvedIdent {
+        let ident = self.insert_ident(name);
+        self.spans.insert(ident.id(), span);
+        self.resolved += 1;
+        ident
+    }
+
+    /// Explain why `name` is unbound here
+    fn unbound(&self, name: String) -> UnboundName {
+        let elsewhere = if let Some((_, span)) = self.defining.iter().rev().find(|(defined, _)| *defined == name) {
+            Some(Elsewhere::Unannotated(*span))
+        } else if let Some((_, span)) = self.binders[self.resolved..].iter().find(|(bound, _)| *bound == name) {
+            Some(Elsewhere::Later(*span))
+        } else {
+            self.closed.get(&name).map(|span| Elsewhere::Sibling(*span))
+        };
+
+        // Names within a third of the length of `name` of it, at least one edit away,
+        // which is not replacing every character
+        let length = name.chars().count();
+        let max_distance = (length / 3).max(1).min(length.saturating_sub(1));
+        let mut candidates: Vec<(usize, &String)> = self.scopes.iter()
+            .flat_map(|scope| scope.keys())
+            .map(|visible| (edit_distance(&name, visible), visible))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+
+        let suggestions = candidates.into_iter().take(3).map(|(_, visible)| visible.clone()).collect();
+        UnboundName { name, suggestions, elsewhere }
     }
 
     fn lookup_ident(&self, name: &str) -> Option<ResolvedIdent> {
@@ -68,10 +182,15 @@ impl NameResolver {
         None
     }
 
-    fn insert_ident(&mut self, name: String) -> ResolvedIdent {
+    /// Allocate a unique identifier without making it visible in any scope
+    fn fresh_ident(&mut self, name: String) -> ResolvedIdent {
         let id = self.counter;
         self.counter += 1;
-        let ident = ResolvedIdent::new(name, id.to_string());
+        ResolvedIdent::new(name, id.to_string())
+    }
+
+    fn insert_ident(&mut self, name: String) -> ResolvedIdent {
+        let ident = self.fresh_ident(name);
 
         self.scopes
             .last_mut()
@@ -81,42 +200,95 @@ impl NameResolver {
         ident
     }
 
+    pub fn resolve_program(&mut self, program: InputProgram) -> Result<ResolvedProgram, FrontendError> {
+        for decl in &program.decls {
+            if let Decl::Instance(instance) = decl {
+                for (_, value) in &instance.methods {
+                    binders(value, &mut self.binders);
+                }
+            }
+        }
+        binders(&program.body, &mut self.binders);
+
+        let mut decls = Vec::new();
+
+        for decl in program.decls {
+            match decl {
+                Decl::Type(decl) => decls.push(Decl::Type(decl)),
+                Decl::Class(ClassDecl { name, type_var, methods }) => {
+                    // Class methods are visible everywhere after the declaration
+                    let methods = methods
+                        .into_iter()
+                        .map(|(method, ty)| (self.insert_ident(method), ty))
+                        .collect();
+
+                    decls.push(Decl::Class(ClassDecl { name, type_var, methods }));
+                }
+                Decl::Instance(InstanceDecl { class, ty, methods }) => {
+                    let mut resolved_methods = Vec::new();
+
+                    for (method, value) in methods {
+                        // Each implementation gets its own identifier, it is
+                        // only reachable through the class method
+                        let impl_ident = self.fresh_ident(method);
+                        resolved_methods.push((impl_ident, self.resolve(value)?));
+                    }
+
+                    decls.push(Decl::Instance(InstanceDecl { class, ty, methods: resolved_methods }));
+                }
+            }
+        }
+
+        Ok(Program {
+            decls,
+            body: self.resolve(program.body)?,
+        })
+    }
+
     pub fn resolve(&mut self, ast: InputASTExpr) -> Result<ResolvedASTExpr, FrontendError> {
         match ast {
-            InputASTExpr::Atom(atom, ty) => {
+            InputASTExpr::Atom(atom, ty, _) => {
                 match atom {
                     ASTAtom::Var(name) => {
                         if let Some(ident) = self.lookup_ident(&name) {
-                            Ok(ASTExpr::Atom(ASTAtom::Var(ident), ty))
+                            Ok(ASTExpr::Atom(ASTAtom::Var(ident), ty, vec![]))
                         } else {
                             // Unbound variable
-                            Err(FrontendError::UnboundVariable(name))
+                            Err(FrontendError::UnboundVariable(self.unbound(name)))
                         }
                     },
-                    ASTAtom::Int(int) => Ok(ASTExpr::Atom(ASTAtom::Int(int), ty)),
-                    ASTAtom::Op(op) => Ok(ASTExpr::Atom(ASTAtom::Op(op), ty)),
-                    ASTAtom::Unit => Ok(ASTExpr::Atom(ASTAtom::Unit, ty)),
-                    ASTAtom::Bool(bool) => Ok(ASTExpr::Atom(ASTAtom::Bool(bool), ty)),
+                    ASTAtom::Int(int) => Ok(ASTExpr::Atom(ASTAtom::Int(int), ty, vec![])),
+                    ASTAtom::Op(op) => Ok(ASTExpr::Atom(ASTAtom::Op(op), ty, vec![])),
+                    ASTAtom::Unit => Ok(ASTExpr::Atom(ASTAtom::Unit, ty, vec![])),
+                    ASTAtom::Bool(bool) => Ok(ASTExpr::Atom(ASTAtom::Bool(bool), ty, vec![])),
+                    ASTAtom::Str(s) => Ok(ASTExpr::Atom(ASTAtom::Str(s), ty, vec![])),
+                    ASTAtom::Hole(name, span) => Ok(ASTExpr::Atom(ASTAtom::Hole(name, span), ty, vec![])),
                 }
             }
-            InputASTExpr::If { cond, then, else_, ty } => {
+            InputASTExpr::If { cond, then, else_, ty, span } => {
                 Ok(ASTExpr::If {
                     cond: Box::new(self.resolve(*cond)?),
                     then: Box::new(self.resolve(*then)?),
                     else_: Box::new(self.resolve(*else_)?),
                     ty,
+                    span,
                 })
             }
-            InputASTExpr::Let { bind: (ident, bind_ty), value, body, ty } => {
+            InputASTExpr::Let { bind: (ident, bind_ty), value, body, ty, ty_params, span } => {
                 // Push a new scope for the let binding
                 self.push_scope();
 
-                // Insert the binding into the current scope
-                let resolved_ident = self.insert_ident(ident.clone());
+                // Only an annotated binding is in scope in its value, where it may be recursive
+                let (resolved_ident, resolved_value) = if bind_ty.is_some() {
+                    let resolved_ident = self.bind(ident.clone(), span);
+                    (resolved_ident, self.resolve(*value)?)
+                } else {
+                    self.defining.push((ident.clone(), span));
+                    let resolved_value = self.resolve(*value);
+                    self.defining.pop();
+                    (self.bind(ident.clone(), span), resolved_value?)
+                };
 
-                // First resolve value without inserting the binding
-                let resolved_value = self.resolve(*value)?;
-                
                 // Resolve the body with the new binding
                 let resolved_body = self.resolve(*body)?;
 
@@ -129,9 +301,11 @@ impl NameResolver {
                     value: Box::new(resolved_value),
                     body: Box::new(resolved_body),
                     ty,
+                    ty_params,
+                    span,
                 })
             }
-            InputASTExpr::Apply { func, args, ty } => {
+            InputASTExpr::Apply { func, args, ty, span } => {
                 let resolved_func = self.resolve(*func)?;
                 let resolved_args = self.resolve(*args)?;
 
@@ -139,14 +313,15 @@ impl NameResolver {
                     func: Box::new(resolved_func),
                     args: Box::new(resolved_args),
                     ty,
+                    span,
                 })
             }
-            InputASTExpr::Lambda { arg, body, ret_ty } => {
+            InputASTExpr::Lambda { arg, body, ret_ty, span } => {
                 // Push a new scope for the lambda
                 self.push_scope();
 
                 // Insert the argument into the current scope
-                let resolved_ident = self.insert_ident(arg.0.clone());
+                let resolved_ident = self.bind(arg.0.clone(), span);
 
                 // Resolve the body with the new binding
                 let resolved_body = self.resolve(*body)?;
@@ -159,8 +334,59 @@ impl NameResolver {
                     arg: (resolved_ident, arg.1),
                     body: Box::new(resolved_body),
                     ret_ty,
+                    span,
                 })
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// The bindings of `expr`, in the order the resolver meets them
+fn binders(expr: &InputASTExpr, out: &mut Vec<(String, Span)>) {
+    match expr {
+        ASTExpr::Atom(..) => {}
+        ASTExpr::If { cond, then, else_, .. } => {
+            binders(cond, out);
+            binders(then, out);
+            binders(else_, out);
+        }
+        ASTExpr::Let { bind: (ident, scheme), value, body, span, .. } => {
+            // In the order the resolver binds them
+            if scheme.is_some() {
+                out.push((ident.clone(), *span));
+                binders(value, out);
+            } else {
+                binders(value, out);
+                out.push((ident.clone(), *span));
+            }
+            binders(body, out);
+        }
+        ASTExpr::Apply { func, args, .. } => {
+            binders(func, out);
+            binders(args, out);
+        }
+        ASTExpr::Lambda { arg: (ident, _), body, span, .. } => {
+            out.push((ident.clone(), *span));
+            binders(body, out);
+        }
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use crate::frontend::ast::ASTExpr;
+use crate::frontend::name_resolution::NameIdentifier;
+use crate::frontend::ty::{Scheme, Ty, TypeVar};
+use crate::frontend::tyck::diagnostic::{skolem_name, TyPrinter};
+use crate::frontend::tyck::tyck::TypedASTExpr;
+use crate::frontend::tyck::type_class::MethodTable;
+
+/// An interface listing the final scheme of every let binding of a typed program, one
+/// `val name : scheme` per line in source order. Bindings local to a definition are
+/// indented below it. Instance methods, bound around the program, are left out.
+/// `binding_types` are the types of the bindings with their effects, see `infer_effects`.
+///
+/// Type variables are named `a`, `b`, ... in order of appearance within each top-level
+/// definition and the bindings local to it, so that the signatures of a definition do
+/// not depend on the rest of the program.
+pub fn signatures(program: &TypedASTExpr, methods: &MethodTable, binding_types: &HashMap<NameIdentifier, Ty>) -> String {
+    let mut lines = Vec::new();
+    collect(program, 0, methods, binding_types, &mut lines);
+
+    let mut out = String::new();
+    let mut rest = lines.as_slice();
+
+    while !rest.is_empty() {
+        let length = 1 + rest[1..].iter().take_while(|(depth, _, _)| *depth > 0).count();
+        let (definition, next) = rest.split_at(length);
+        rest = next;
+
+        // Skolems of an annotation are written as its variables, which keep their names
+        let rigid: HashMap<TypeVar, String> = definition.iter()
+            .flat_map(|(_, _, scheme)| scheme.ty.free_vars())
+            .filter(|var| var.contains('#'))
+            .flat_map(|skolem| [skolem_name(&skolem).to_string(), skolem])
+            .map(|var| (var, String::new()))
+            .collect();
+
+        let mut printer = TyPrinter::source(&rigid);
+        for (depth, name, scheme) in definition {
+            out.push_str(&format!("{}val {} : {}\n", "  ".repeat(*depth), name, printer.scheme(scheme)));
+        }
+    }
+
+    out
+}
+
+fn collect(
+    expr: &TypedASTExpr,
+    depth: usize,
+    methods: &MethodTable,
+    binding_types: &HashMap<NameIdentifier, Ty>,
+    lines: &mut Vec<(usize, String, Scheme)>,
+) {
+    match expr {
+        ASTExpr::Atom(..) => {}
+        ASTExpr::If { cond, then, else_, .. } => {
+            collect(cond, depth, methods, binding_types, lines);
+            collect(then, depth, methods, binding_types, lines);
+            collect(else_, depth, methods, binding_types, lines);
+        }
+        ASTExpr::Let { bind: (ident, scheme), value, body, .. } => {
+            if methods.is_impl(&ident.id) {
+                collect(body, depth, methods, binding_types, lines);
+                return;
+            }
+
+            // Only generalized or annotated bindings carry a scheme
+            let scheme = scheme.clone().unwrap_or_else(|| Scheme {
+                vars: vec![],
+                constraints: vec![],
+                ty: binding_types.get(&ident.id).cloned().unwrap_or_else(|| value.ty()),
+            });
+
+            lines.push((depth, ident.name.clone(), scheme));
+            collect(value, depth + 1, methods, binding_types, lines);
+            collect(body, depth, methods, binding_types, lines);
+        }
+        ASTExpr::Apply { func, args, .. } => {
+            collect(func, depth, methods, binding_types, lines);
+            collect(args, depth, methods, binding_types, lines);
+        }
+        ASTExpr::Lambda { body, .. } => collect(body, depth, methods, binding_types, lines),
+    }
+}
+
@@ -0,0 +1,65 @@
+use crate::frontend::ast::{ASTAtom, ClassDecl, OpType, Span, TypeDecl};
+use crate::frontend::ty::{Scheme, Ty};
+
+/// An expression as written in the source. Operators, functions of several
+/// parameters, applications to several arguments and parentheses are kept,
+/// `desugar` lowers them to the core `InputASTExpr`.
+#[derive(Debug, Clone)]
+pub enum SurfaceExpr {
+    Atom(ASTAtom<String>),
+    /// `(e)`
+    Paren(Box<SurfaceExpr>),
+    If {
+        cond: Box<SurfaceExpr>,
+        then: Box<SurfaceExpr>,
+        else_: Box<SurfaceExpr>,
+        span: Span,
+    },
+    Let {
+        bind: (String, Option<Scheme>),
+        value: Box<SurfaceExpr>,
+        body: Box<SurfaceExpr>,
+        span: Span,
+    },
+    /// `f a b`, a function applied to all of its arguments
+    Apply {
+        func: Box<SurfaceExpr>,
+        args: Vec<SurfaceExpr>,
+        span: Span,
+    },
+    /// `l + r`
+    BinOp {
+        op: OpType,
+        left: Box<SurfaceExpr>,
+        right: Box<SurfaceExpr>,
+        span: Span,
+    },
+    /// `fun x (y: Int) -> e`, with every parameter
+    Lambda {
+        args: Vec<(String, Option<Ty>)>,
+        body: Box<SurfaceExpr>,
+        span: Span,
+    },
+}
+
+/// `instance Show Int where show = fun x -> "int" end`, as written
+#[derive(Debug, Clone)]
+pub struct SurfaceInstanceDecl {
+    pub class: String,
+    pub ty: Ty,
+    pub methods: Vec<(String, SurfaceExpr)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SurfaceDecl {
+    Type(TypeDecl),
+    Class(ClassDecl<String>),
+    Instance(SurfaceInstanceDecl),
+}
+
+/// A whole source file as parsed
+#[derive(Debug, Clone)]
+pub struct SurfaceProgram {
+    pub decls: Vec<SurfaceDecl>,
+    pub body: SurfaceExpr,
+}
@@ -7,15 +7,79 @@ use crate::frontend::tyck::type_class::TypeClassConstraint;
 
 pub type TypeVar = String;
 
+/// A unification variable, an index into the checker's `UnionFind`
+pub type VarId = usize;
+
+/// The type constructor standing for every type variable of a function compiled
+/// to a single uniform implementation. Its values are boxed, i.e. behind a pointer.
+pub const BOXED: &str = "boxed";
+
+pub fn boxed_ty() -> Ty {
+    Ty::App(BOXED.to_string(), vec![])
+}
+
+/// The side effects a function may have when it is called: a set of `io`, `state`
+/// and `div` (may not terminate), empty for a pure function. Effects annotate arrow
+/// types but are not part of their identity: they are ignored by type equality and
+/// unification, and inferred once the program is typed (see `frontend::effect`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Effect(u8);
+
+impl Effect {
+    pub const PURE: Effect = Effect(0);
+    pub const IO: Effect = Effect(1);
+    pub const STATE: Effect = Effect(1 << 1);
+    pub const DIVERGE: Effect = Effect(1 << 2);
+
+    pub fn union(self, other: Effect) -> Effect {
+        Effect(self.0 | other.0)
+    }
+
+    pub fn contains(self, other: Effect) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_pure(self) -> bool {
+        self == Effect::PURE
+    }
+}
+
+impl Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_pure() {
+            return write!(f, "pure");
+        }
+
+        let names: Vec<&str> = [(Effect::IO, "io"), (Effect::STATE, "state"), (Effect::DIVERGE, "div")]
+            .into_iter()
+            .filter(|(effect, _)| self.contains(*effect))
+            .map(|(_, name)| name)
+            .collect();
+
+        write!(f, "{}", names.join(", "))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Ty {
     Unit,
     Int,
     Bool,
-    Arrow(Box<Ty>, Box<Ty>),
+    String,
+    /// A function type, with the effects of calling the function
+    Arrow(Box<Ty>, Box<Ty>, Effect),
 
     /// A monomorphic type, or a type variable
     Mono(TypeVar),
+
+    /// A unification variable, only found in types being inferred
+    Var(VarId),
+
+    /// A type constructor applied to its arguments, e.g. `List Int`
+    App(String, Vec<Ty>),
+
+    /// A polymorphic type nested in another type, e.g. the argument of `(forall a. a -> a) -> Int`
+    Forall(Vec<TypeVar>, Box<Ty>),
 }
 
 impl PartialEq for Ty {
@@ -24,8 +88,12 @@ impl PartialEq for Ty {
             (Ty::Unit, Ty::Unit) => true,
             (Ty::Int, Ty::Int) => true,
             (Ty::Bool, Ty::Bool) => true,
-            (Ty::Arrow(l1, r1), Ty::Arrow(l2, r2)) => { l1 == l2 && r1 == r2 },
+            (Ty::String, Ty::String) => true,
+            (Ty::Arrow(l1, r1, _), Ty::Arrow(l2, r2, _)) => { l1 == l2 && r1 == r2 },
             (Ty::Mono(tv1), Ty::Mono(tv2)) => tv1 == tv2,
+            (Ty::Var(id1), Ty::Var(id2)) => id1 == id2,
+            (Ty::App(n1, a1), Ty::App(n2, a2)) => n1 == n2 && a1 == a2,
+            (Ty::Forall(v1, t1), Ty::Forall(v2, t2)) => v1 == v2 && t1 == t2,
             _ => false,
         }
     }
@@ -41,11 +109,21 @@ impl Hash for Ty {
             Ty::Unit => (),
             Ty::Int => (),
             Ty::Bool => (),
-            Ty::Arrow(l, r) => {
+            Ty::String => (),
+            Ty::Arrow(l, r, _) => {
                 l.hash(state);
                 r.hash(state);
             }
             Ty::Mono(tv) => tv.hash(state),
+            Ty::Var(id) => id.hash(state),
+            Ty::App(name, args) => {
+                name.hash(state);
+                args.hash(state);
+            }
+            Ty::Forall(vars, ty) => {
+                vars.hash(state);
+                ty.hash(state);
+            }
         }
     }
 }
@@ -56,15 +134,40 @@ impl Display for Ty {
             Ty::Unit => write!(f, "unit"),
             Ty::Int => write!(f, "int"),
             Ty::Bool => write!(f, "bool"),
+            Ty::String => write!(f, "string"),
             Ty::Mono(tv) => write!(f, "{tv}"),
-            Ty::Arrow(t1, t2) => {
+            Ty::Var(id) => write!(f, "t{id}"),
+            Ty::Arrow(t1, t2, effect) => {
                 // Add parentheses around the left type if it is another Arrow
                 let left = match **t1 {
-                    Ty::Arrow(_, _) => format!("({})", t1),
+                    Ty::Arrow(..) | Ty::Forall(_, _) => format!("({})", t1),
                     _ => format!("{}", t1),
                 };
-                write!(f, "{} -> {}", left, t2)
+                let right = match **t2 {
+                    Ty::Forall(_, _) => format!("({})", t2),
+                    _ => format!("{}", t2),
+                };
+                if effect.is_pure() {
+                    write!(f, "{} -> {}", left, right)
+                } else {
+                    write!(f, "{} -{{{}}}-> {}", left, effect, right)
+                }
+            }
+            Ty::App(name, args) => {
+                write!(f, "{}", name)?;
+
+                // Arguments that are not atomic are parenthesized
+                for arg in args {
+                    match arg {
+                        Ty::Arrow(..) | Ty::Forall(..) => write!(f, " ({})", arg)?,
+                        Ty::App(_, inner) if !inner.is_empty() => write!(f, " ({})", arg)?,
+                        _ => write!(f, " {}", arg)?,
+                    }
+                }
+
+                Ok(())
             }
+            Ty::Forall(vars, ty) => write!(f, "forall {}. {}", vars.join(" "), ty),
         }
     }
 }
@@ -72,49 +175,64 @@ impl Display for Ty {
 impl Ty {
     pub fn contains_var(&self, var: &TypeVar) -> bool {
         match self {
-            Ty::Unit | Ty::Int | Ty::Bool => false,
-            Ty::Arrow(left, right) => left.contains_var(var) || right.contains_var(var),
+            Ty::Unit | Ty::Int | Ty::Bool | Ty::String | Ty::Var(_) => false,
+            Ty::Arrow(left, right, _) => left.contains_var(var) || right.contains_var(var),
             Ty::Mono(v) => v == var,
-        }
-    }
-
-    pub fn apply(&self, var: &TypeVar, ty: &Ty) -> Ty {
-        match self {
-            Ty::Mono(v) if v == var => ty.clone(),
-            Ty::Arrow(left, right) => Ty::Arrow(
-                Box::new(left.apply(var, ty)),
-                Box::new(right.apply(var, ty)),
-            ),
-            _ => self.clone(),
+            Ty::App(_, args) => args.iter().any(|arg| arg.contains_var(var)),
+            Ty::Forall(vars, ty) => !vars.contains(var) && ty.contains_var(var),
         }
     }
 
     pub fn free_vars(&self) -> HashSet<TypeVar> {
         match self {
-            Ty::Unit | Ty::Int | Ty::Bool => HashSet::new(),
+            Ty::Unit | Ty::Int | Ty::Bool | Ty::String | Ty::Var(_) => HashSet::new(),
             Ty::Mono(var) => {
                 let mut vars = HashSet::new();
                 vars.insert(var.clone());
                 vars
             }
-            Ty::Arrow(left, right) => {
+            Ty::Arrow(left, right, _) => {
                 let mut vars = left.free_vars();
                 vars.extend(right.free_vars());
                 vars
             }
+            Ty::App(_, args) => args.iter().flat_map(|arg| arg.free_vars()).collect(),
+            Ty::Forall(bound, ty) => {
+                let mut vars = ty.free_vars();
+                vars.retain(|var| !bound.contains(var));
+                vars
+            }
         }
     }
 
-    pub fn extract_args(&self) -> (Vec<Ty>, Ty) {
-        let mut args = Vec::new();
-        let mut current = self;
+    /// Whether a polymorphic type occurs anywhere inside this type
+    pub fn is_higher_rank(&self) -> bool {
+        match self {
+            Ty::Forall(..) => true,
+            Ty::Arrow(left, right, _) => left.is_higher_rank() || right.is_higher_rank(),
+            Ty::App(_, args) => args.iter().any(|arg| arg.is_higher_rank()),
+            _ => false,
+        }
+    }
 
-        while let Ty::Arrow(arg, ret) = current {
-            args.push(*arg.clone());
-            current = ret;
+    /// Every effect a value of this type may have when called, however it is applied
+    pub fn latent_effect(&self) -> Effect {
+        match self {
+            // Functions taken as arguments are accounted for by whoever passes them
+            Ty::Arrow(_, right, effect) => effect.union(right.latent_effect()),
+            Ty::App(_, args) => args.iter().fold(Effect::PURE, |effect, arg| effect.union(arg.latent_effect())),
+            Ty::Forall(_, body) => body.latent_effect(),
+            _ => Effect::PURE,
         }
+    }
 
-        (args, current.clone())
+    /// The effects of applying a function of this type to `arity` arguments at once
+    pub fn call_effect(&self, arity: usize) -> Effect {
+        match self {
+            Ty::Arrow(_, right, effect) if arity > 0 => effect.union(right.call_effect(arity - 1)),
+            Ty::Forall(_, body) => body.call_effect(arity),
+            _ => Effect::PURE,
+        }
     }
 
     pub fn mangle(&self) -> String {
@@ -122,12 +240,19 @@ impl Ty {
             Ty::Unit => "unit".to_string(),
             Ty::Int => "int".to_string(),
             Ty::Bool => "bool".to_string(),
+            Ty::String => "string".to_string(),
             Ty::Mono(var) => format!("tv{}", var),
-            Ty::Arrow(left, right) => {
+            Ty::Var(id) => format!("tvt{}", id),
+            Ty::Arrow(left, right, _) => {
                 let left_mangled = left.mangle();
                 let right_mangled = right.mangle();
                 format!("fn_{}_to_{}_nf", left_mangled, right_mangled)
             }
+            Ty::App(name, args) => {
+                let args_mangled: Vec<String> = args.iter().map(|arg| arg.mangle()).collect();
+                format!("{}_of_{}_na", name, args_mangled.join("_"))
+            }
+            Ty::Forall(vars, ty) => format!("forall_{}_{}_nf", vars.join("_"), ty.mangle()),
         }
     }
 }
@@ -187,6 +312,10 @@ impl TypingContext {
         self.mapping.insert(id, scheme);
     }
 
+    pub fn remove(&mut self, id: &NameIdentifier) {
+        self.mapping.remove(id);
+    }
+
     pub fn get(&self, id: &NameIdentifier) -> Option<&Scheme> {
         self.mapping.get(id)
     }
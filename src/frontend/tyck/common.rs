@@ -0,0 +1,358 @@
+use std::collections::{HashMap, HashSet};
+use crate::frontend::ast::{ClassDecl, InstanceDecl};
+use crate::frontend::FrontendError;
+use crate::frontend::name_resolution::{ResolvedASTExpr, ResolvedIdent};
+use crate::frontend::ty::{Scheme, Ty, TypeVar, TypingContext};
+use crate::frontend::tyck::diagnostic::{skolem_name, Origin, TyPrinter};
+use crate::frontend::tyck::subst::apply_subst;
+use crate::frontend::tyck::type_class::{is_comparison_class, method_scheme, ClassEnv, ClassInfo, TypeClassConstraint, Wanted};
+use crate::frontend::tyck::union_find::UnionFind;
+
+/// An instance method, to be checked against the signature of the class method it implements
+pub struct MethodImpl {
+    pub ident: ResolvedIdent,
+    pub value: ResolvedASTExpr,
+    pub expected: Ty,
+    pub origin: Origin,
+}
+
+/// The parts of type checking that the bidirectional and the hm checker share:
+/// schemes, class constraints, declarations and the store of type variables.
+/// A checker provides its state and how it unifies types, the rest is implemented here once.
+pub trait Inference {
+    fn context(&self) -> &TypingContext;
+
+    fn classes(&self) -> &ClassEnv;
+
+    fn classes_mut(&mut self) -> &mut ClassEnv;
+
+    /// The solutions of the type variables
+    fn subst(&self) -> &UnionFind;
+
+    fn subst_mut(&mut self) -> &mut UnionFind;
+
+    /// Class constraints arising from uses of constrained bindings
+    fn wanted(&mut self) -> &mut Vec<Wanted>;
+
+    /// Class constraints assumed while checking annotated bindings
+    fn given(&self) -> &[TypeClassConstraint];
+
+    /// Type variables of the enclosing annotations, mapped to their skolems
+    fn scoped(&self) -> &HashMap<TypeVar, TypeVar>;
+
+    /// Skolems of the quantified variables of annotations, mapped to what binds them
+    fn rigid(&self) -> &HashMap<TypeVar, String>;
+
+    fn is_rigid(&self, var: &TypeVar) -> bool {
+        self.rigid().contains_key(var)
+    }
+
+    /// A rigid type variable as shown in diagnostics
+    fn describe_rigid(&self, var: &TypeVar) -> String {
+        match self.rigid().get(var) {
+            Some(binder) => format!("{} (bound by {})", skolem_name(var), binder),
+            None => var.clone(),
+        }
+    }
+
+    /// Check a type written in the program
+    fn annotation(&self, ty: &Ty) -> Result<(), FrontendError>;
+
+    /// Bring `ident` into scope with type `scheme`
+    fn bind_var(&mut self, ident: &ResolvedIdent, scheme: Scheme);
+
+    /// `ty` with the current solution of its type variables
+    fn resolve(&self, ty: Ty) -> Ty {
+        self.subst().apply(ty)
+    }
+
+    fn fresh_ty(&mut self) -> Ty {
+        Ty::Var(self.subst_mut().fresh())
+    }
+
+    /// Whether `ty` is a flexible type variable: a unification variable, or a variable
+    /// of an annotation that no enclosing annotation binds
+    fn is_flexible(&self, ty: &Ty) -> bool {
+        match ty {
+            Ty::Var(_) => true,
+            Ty::Mono(var) => !self.is_rigid(var),
+            _ => false,
+        }
+    }
+
+    /// Solve the flexible type variable `var` to `ty`, unless `var` occurs in it
+    fn solve_var(&mut self, var: &Ty, ty: Ty) -> Result<(), FrontendError> {
+        // Variables of annotations are solved like unification variables
+        for ty in [var, &ty] {
+            if let Ty::Mono(name) = ty && !self.is_rigid(name) {
+                self.subst_mut().declare(name);
+            }
+        }
+
+        let root = self.subst().class(var).expect("only flexible type variables are solved");
+
+        // A flexible variable joins the class of `var`
+        if self.subst().class(&ty) == Some(root) {
+            return Ok(());
+        }
+
+        if self.subst().occurs(root, &ty) {
+            return Err(FrontendError::TypeError(format!(
+                "Cannot unify {} with {}, occurs check failed",
+                var, ty
+            )));
+        }
+
+        self.subst_mut().bind(root, ty);
+        Ok(())
+    }
+
+    /// Instantiate a type scheme by replacing its type variables with fresh type variables.
+    /// The class constraints of the scheme are returned on the fresh variables, and so are
+    /// the variables themselves, in the order of the scheme's: the type arguments of the use.
+    fn instantiate(&mut self, scheme: &Scheme) -> (Ty, Vec<Wanted>, Vec<Ty>) {
+        let ty_args: Vec<Ty> = scheme.vars.iter().map(|_| self.fresh_ty()).collect();
+        let subst: HashMap<TypeVar, Ty> = scheme.vars.iter().cloned().zip(ty_args.iter().cloned()).collect();
+
+        let constraints = scheme.constraints.iter().map(|constraint| Wanted {
+            class: constraint.class.clone(),
+            ty: subst.get(&constraint.type_var)
+                .cloned()
+                .unwrap_or_else(|| Ty::Mono(constraint.type_var.clone())),
+        }).collect();
+
+        (apply_subst(scheme.ty.clone(), &subst), constraints, ty_args)
+    }
+
+    /// Rename the type variables of an annotation that are bound by an enclosing annotation,
+    /// except those in `bound`. Its other free variables are flexible from here on.
+    fn scoped_ty(&mut self, ty: Ty, bound: &[TypeVar]) -> Ty {
+        let subst = self.scoped().iter()
+            .filter(|(var, _)| !bound.contains(var))
+            .map(|(var, skolem)| (var.clone(), Ty::Mono(skolem.clone())))
+            .collect();
+
+        let ty = apply_subst(ty, &subst);
+
+        // Declared at the level of the annotation, so that an enclosing let generalizes them
+        for var in ty.free_vars() {
+            if !bound.contains(&var) && !self.is_rigid(&var) {
+                self.subst_mut().declare(&var);
+            }
+        }
+
+        ty
+    }
+
+    /// Type variables that are free in the context
+    fn context_free_vars(&self) -> HashSet<TypeVar> {
+        let mut vars = HashSet::new();
+
+        for scheme in self.context().get_mapping().values() {
+            for var in self.resolve(scheme.ty.clone()).free_vars() {
+                if !scheme.vars.contains(&var) {
+                    vars.insert(var);
+                }
+            }
+        }
+
+        vars
+    }
+
+    /// After checking against `ty`, none of its skolems may be free in the context
+    fn check_escape(&self, skolems: &[TypeVar], ty: &Ty) -> Result<(), FrontendError> {
+        let context_vars = self.context_free_vars();
+
+        match skolems.iter().find(|skolem| context_vars.contains(*skolem)) {
+            Some(skolem) => Err(FrontendError::TypeError(format!(
+                "Rigid type variable {} escapes its scope in {}, the expression is not polymorphic enough",
+                self.describe_rigid(skolem), ty
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Quantify `ty` over its type variables introduced since the let level was left,
+    /// which the context cannot reach. Class constraints on those variables, raised
+    /// since `wanted_start`, become constraints of the scheme.
+    fn generalize(&mut self, ty: Ty, wanted_start: usize) -> Scheme {
+        let ty = self.resolve(ty);
+        let (mut vars, ty) = self.subst().generalize(ty);
+        vars.sort();
+
+        let mut constraints: Vec<TypeClassConstraint> = Vec::new();
+
+        for constraint in self.wanted().split_off(wanted_start) {
+            let type_var = match self.resolve(constraint.ty.clone()) {
+                Ty::Var(id) => Some(self.subst().name(id)),
+                Ty::Mono(var) => Some(var),
+                _ => None,
+            };
+
+            match type_var {
+                Some(var) if vars.contains(&var) => {
+                    let constraint = TypeClassConstraint { class: constraint.class, type_var: var };
+
+                    if !constraints.contains(&constraint) {
+                        constraints.push(constraint);
+                    }
+                }
+                _ => self.wanted().push(constraint),
+            }
+        }
+
+        Scheme { vars, constraints, ty }
+    }
+
+    /// The given class constraints, on the types their variables are solved to
+    fn given_types(&self) -> HashSet<(String, Ty)> {
+        self.given().iter()
+            .map(|c| (c.class.clone(), self.resolve(Ty::Mono(c.type_var.clone()))))
+            .collect()
+    }
+
+    /// Drop the class constraints raised since `wanted_start` that a given constraint satisfies
+    fn discharge_given(&mut self, wanted_start: usize) {
+        let given = self.given_types();
+
+        for constraint in self.wanted().split_off(wanted_start) {
+            let ty = self.resolve(constraint.ty.clone());
+
+            if !given.contains(&(constraint.class.clone(), ty)) {
+                self.wanted().push(constraint);
+            }
+        }
+    }
+
+    /// Check that every wanted class constraint is satisfied, either by an instance
+    /// or by a constraint given in a type annotation
+    fn solve_classes(&mut self) -> Result<(), FrontendError> {
+        let given = self.given_types();
+        let wanted = std::mem::take(self.wanted());
+
+        for constraint in wanted {
+            let ty = self.resolve(constraint.ty);
+
+            if given.contains(&(constraint.class.clone(), ty.clone())) {
+                continue;
+            }
+
+            // Types are shown with the names of the other diagnostics
+            let shown = TyPrinter::new(self.rigid()).ty(&ty);
+
+            match ty {
+                Ty::Mono(var) if self.is_rigid(&var) => {
+                    return Err(FrontendError::TypeError(format!(
+                        "Cannot deduce ({} {}) for rigid type variable {}, add the constraint to its annotation",
+                        constraint.class, shown, self.describe_rigid(&var)
+                    )));
+                }
+                Ty::Mono(_) | Ty::Var(_) => {
+                    return Err(FrontendError::TypeError(format!(
+                        "Ambiguous type variable {}: cannot deduce ({} {}), add it to the type annotation",
+                        shown, constraint.class, shown
+                    )));
+                }
+                Ty::Arrow(..) if is_comparison_class(&constraint.class) => {
+                    return Err(FrontendError::TypeError(format!(
+                        "Values of function type {} cannot be compared: no instance for ({} ({}))",
+                        shown, constraint.class, shown
+                    )));
+                }
+                ty if !self.classes().has_instance(&constraint.class, &ty) => {
+                    let shown = match ty {
+                        Ty::Arrow(..) | Ty::Forall(..) => format!("({})", shown),
+                        Ty::App(_, args) if !args.is_empty() => format!("({})", shown),
+                        _ => shown,
+                    };
+
+                    return Err(FrontendError::TypeError(format!(
+                        "No instance for ({} {})",
+                        constraint.class, shown
+                    )));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn declare_class(&mut self, class: ClassDecl<ResolvedIdent>) -> Result<(), FrontendError> {
+        let ClassDecl { name, type_var, methods } = class;
+
+        for (method, ty) in &methods {
+            self.annotation(ty)?;
+
+            if !ty.contains_var(&type_var) {
+                return Err(FrontendError::TypeError(format!(
+                    "Method {} of class {} does not mention the class parameter {}",
+                    method.name, name, type_var
+                )));
+            }
+
+            self.bind_var(method, method_scheme(&name, &type_var, ty));
+        }
+
+        self.classes_mut().add_class(name, ClassInfo { type_var, methods })
+    }
+
+    /// Declare an instance, returning its methods with the types the class requires of them.
+    /// The methods are recorded as implementations of the class methods at the instance type.
+    fn declare_instance(
+        &mut self,
+        instance: InstanceDecl<ResolvedIdent, Option<Ty>>,
+    ) -> Result<Vec<MethodImpl>, FrontendError> {
+        let InstanceDecl { class, ty: instance_ty, methods } = instance;
+
+        let class_info = self.classes().get_class(&class).cloned().ok_or_else(|| {
+            FrontendError::TypeError(format!("Instance of unknown class {}", class))
+        })?;
+
+        self.annotation(&instance_ty)?;
+
+        if !instance_ty.free_vars().is_empty() {
+            return Err(FrontendError::TypeError(format!(
+                "Instance type must be concrete, found {} {}",
+                class, instance_ty
+            )));
+        }
+
+        self.classes_mut().add_instance(&class, instance_ty.clone())?;
+
+        for (method, _) in &class_info.methods {
+            if !methods.iter().any(|(impl_ident, _)| impl_ident.name == method.name) {
+                return Err(FrontendError::TypeError(format!(
+                    "Missing implementation of {} in instance {} {}",
+                    method.name, class, instance_ty
+                )));
+            }
+        }
+
+        let mut impls = Vec::new();
+
+        for (impl_ident, value) in methods {
+            let (method, method_ty) = class_info.methods.iter()
+                .find(|(method, _)| method.name == impl_ident.name)
+                .ok_or_else(|| FrontendError::TypeError(format!(
+                    "{} is not a method of class {}",
+                    impl_ident.name, class
+                )))?;
+
+            let expected = apply_subst(
+                method_ty.clone(),
+                &HashMap::from([(class_info.type_var.clone(), instance_ty.clone())]),
+            );
+
+            let origin = Origin::Method {
+                method: impl_ident.name.clone(),
+                instance: format!("{} {}", class, instance_ty),
+            };
+
+            self.classes_mut().methods_mut().insert_impl(&method.id, instance_ty.clone(), impl_ident.clone());
+            impls.push(MethodImpl { ident: impl_ident, value, expected, origin });
+        }
+
+        Ok(impls)
+    }
+}
@@ -0,0 +1,399 @@
+use crate::frontend::ty::{Scheme, Ty, TypeVar};
+
+/// The judgement concluded by a step of type checking
+#[derive(Debug, Clone)]
+pub enum Step {
+    /// `Γ ⊢ expr ⇒ ty`, the type is unknown until the step is complete
+    Infer { expr: String, ty: Option<Ty> },
+    /// `Γ ⊢ expr ⇐ ty`
+    Check { expr: String, ty: Ty },
+    /// `expected ~ found`
+    Unify { expected: Ty, found: Ty },
+    /// `var := ty`, a unification variable is solved
+    Bind { var: TypeVar, ty: Ty },
+    /// A use of `name` at an instance `ty` of its scheme
+    Instantiate { name: String, scheme: Scheme, ty: Ty },
+    /// The type of the binding `name` is generalized to `scheme`
+    Generalize { name: String, scheme: Scheme },
+}
+
+/// A judgement with the rule concluding it and the derivations of its premises
+#[derive(Debug, Clone)]
+pub struct Derivation {
+    pub rule: &'static str,
+    pub step: Step,
+    pub premises: Vec<Derivation>,
+    pub failed: bool,
+}
+
+impl Derivation {
+    fn new(rule: &'static str, step: Step) -> Self {
+        Derivation { rule, step, premises: Vec::new(), failed: false }
+    }
+}
+
+/// Builds derivation trees while the type checker runs: a step is entered before its
+/// premises are derived and finished after them
+#[derive(Debug, Default)]
+pub struct Recorder {
+    open: Vec<Derivation>,
+    roots: Vec<Derivation>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Recorder::default()
+    }
+
+    pub fn enter(&mut self, rule: &'static str, step: Step) {
+        self.open.push(Derivation::new(rule, step));
+    }
+
+    /// Finish the innermost open step, completing an inference with the type found
+    pub fn finish(&mut self, inferred: Option<Ty>, failed: bool) {
+        let mut derivation = self.open.pop().expect("No open derivation step");
+
+        if let (Step::Infer { ty, .. }, Some(inferred)) = (&mut derivation.step, inferred) {
+            *ty = Some(inferred);
+        }
+        derivation.failed = failed;
+
+        self.attach(derivation);
+    }
+
+    /// A step without premises
+    pub fn axiom(&mut self, rule: &'static str, step: Step) {
+        self.attach(Derivation::new(rule, step));
+    }
+
+    fn attach(&mut self, derivation: Derivation) {
+        match self.open.last_mut() {
+            Some(parent) => parent.premises.push(derivation),
+            None => self.roots.push(derivation),
+        }
+    }
+
+    pub fn roots(&self) -> &[Derivation] {
+        &self.roots
+    }
+}
+
+/// The derivations of a type checked program, one per instance method and one for the
+/// program itself, with the final solution of every unification variable
+pub struct Derivations {
+    pub roots: Vec<Derivation>,
+    pub substitution: Vec<(TypeVar, Ty)>,
+}
+
+impl Derivations {
+    pub fn to_json(&self) -> String {
+        let roots: Vec<String> = self.roots.iter().map(|root| json_derivation(root, 2)).collect();
+        let substitution: Vec<String> = self.substitution.iter()
+            .map(|(var, ty)| format!(
+                "    {{ \"var\": {}, \"type\": {} }}",
+                json_string(var), json_string(&ty.to_string())
+            ))
+            .collect();
+
+        format!(
+            "{{\n  \"derivations\": [\n{}\n  ],\n  \"substitution\": [\n{}\n  ]\n}}\n",
+            roots.join(",\n"), substitution.join(",\n")
+        )
+    }
+
+    /// A standalone LaTeX document with one `bussproofs` proof tree per derivation
+    pub fn to_latex(&self) -> String {
+        let mut out = String::new();
+        out.push_str("\\documentclass{article}\n\\usepackage{bussproofs}\n\\begin{document}\n\n");
+
+        for root in &self.roots {
+            out.push_str("\\begin{prooftree}\n");
+            latex_derivation(root, &mut out);
+            out.push_str("\\end{prooftree}\n\n");
+        }
+
+        if !self.substitution.is_empty() {
+            out.push_str("\\noindent Substitution:\n\\begin{itemize}\n");
+            for (var, ty) in &self.substitution {
+                out.push_str(&format!("  \\item ${} := {}$\n", latex_var(var), latex_ty(ty)));
+            }
+            out.push_str("\\end{itemize}\n\n");
+        }
+
+        out.push_str("\\end{document}\n");
+        out
+    }
+
+    /// Nested lists, the conclusion of each rule above its premises
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Derivation\n");
+
+        for root in &self.roots {
+            out.push('\n');
+            markdown_derivation(root, 0, &mut out);
+        }
+
+        if !self.substitution.is_empty() {
+            out.push_str("\n## Substitution\n\n| Variable | Type |\n| --- | --- |\n");
+            for (var, ty) in &self.substitution {
+                out.push_str(&format!("| `{}` | `{}` |\n", var, ty));
+            }
+        }
+
+        out
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+fn json_derivation(derivation: &Derivation, indent: usize) -> String {
+    let pad = "  ".repeat(indent);
+    let field = |key: &str, value: &str| format!("{}  {}: {}", pad, json_string(key), json_string(value));
+
+    let mut fields = vec![field("rule", derivation.rule)];
+    match &derivation.step {
+        Step::Infer { expr, ty } => {
+            fields.push(field("judgement", "infer"));
+            fields.push(field("expr", expr));
+            if let Some(ty) = ty {
+                fields.push(field("type", &ty.to_string()));
+            }
+        }
+        Step::Check { expr, ty } => {
+            fields.push(field("judgement", "check"));
+            fields.push(field("expr", expr));
+            fields.push(field("type", &ty.to_string()));
+        }
+        Step::Unify { expected, found } => {
+            fields.push(field("judgement", "unify"));
+            fields.push(field("expected", &expected.to_string()));
+            fields.push(field("found", &found.to_string()));
+        }
+        Step::Bind { var, ty } => {
+            fields.push(field("judgement", "bind"));
+            fields.push(field("var", var));
+            fields.push(field("type", &ty.to_string()));
+        }
+        Step::Instantiate { name, scheme, ty } => {
+            fields.push(field("judgement", "instantiate"));
+            fields.push(field("name", name));
+            fields.push(field("scheme", &scheme.to_string()));
+            fields.push(field("type", &ty.to_string()));
+        }
+        Step::Generalize { name, scheme } => {
+            fields.push(field("judgement", "generalize"));
+            fields.push(field("name", name));
+            fields.push(field("scheme", &scheme.to_string()));
+        }
+    }
+
+    if derivation.failed {
+        fields.push(format!("{}  \"failed\": true", pad));
+    }
+
+    if !derivation.premises.is_empty() {
+        let premises: Vec<String> = derivation.premises.iter()
+            .map(|premise| json_derivation(premise, indent + 2))
+            .collect();
+        fields.push(format!("{}  \"premises\": [\n{}\n{}  ]", pad, premises.join(",\n"), pad));
+    }
+
+    format!("{}{{\n{}\n{}}}", pad, fields.join(",\n"), pad)
+}
+
+/// The judgement in plain text, as in `Γ ⊢ e ⇒ τ`
+fn judgement(step: &Step) -> String {
+    match step {
+        Step::Infer { expr, ty: Some(ty) } => format!("Γ ⊢ `{}` ⇒ `{}`", expr, ty),
+        Step::Infer { expr, ty: None } => format!("Γ ⊢ `{}` ⇒ ?", expr),
+        Step::Check { expr, ty } => format!("Γ ⊢ `{}` ⇐ `{}`", expr, ty),
+        Step::Unify { expected, found } => format!("`{}` ~ `{}`", expected, found),
+        Step::Bind { var, ty } => format!("`{}` := `{}`", var, ty),
+        Step::Instantiate { name, scheme, ty } => format!("`{} : {}` ⊑ `{}`", name, scheme, ty),
+        Step::Generalize { name, scheme } => format!("`{} : {}`", name, scheme),
+    }
+}
+
+fn markdown_derivation(derivation: &Derivation, depth: usize, out: &mut String) {
+    let failed = if derivation.failed { " — failed" } else { "" };
+    out.push_str(&format!(
+        "{}- **{}** {}{}\n",
+        "  ".repeat(depth), derivation.rule, judgement(&derivation.step), failed
+    ));
+
+    for premise in &derivation.premises {
+        markdown_derivation(premise, depth + 1, out);
+    }
+}
+
+// `bussproofs` infers from at most five premises
+const MAX_PREMISES: usize = 5;
+
+fn latex_derivation(derivation: &Derivation, out: &mut String) {
+    let premises = &derivation.premises;
+    let shown = if premises.len() > MAX_PREMISES { MAX_PREMISES - 1 } else { premises.len() };
+
+    for premise in &premises[..shown] {
+        latex_derivation(premise, out);
+    }
+
+    let mut count = shown;
+    if premises.len() > shown {
+        out.push_str(&format!("\\AxiomC{{$\\cdots$ {} more}}\n", premises.len() - shown));
+        count += 1;
+    }
+
+    let label = if derivation.failed {
+        format!("\\textsc{{{}}} (failed)", derivation.rule)
+    } else {
+        format!("\\textsc{{{}}}", derivation.rule)
+    };
+    let conclusion = latex_judgement(&derivation.step);
+
+    if count == 0 {
+        // An axiom is drawn with a rule over nothing, to show its label
+        out.push_str("\\AxiomC{}\n");
+        count = 1;
+    }
+
+    let inference = ["UnaryInfC", "BinaryInfC", "TrinaryInfC", "QuaternaryInfC", "QuinaryInfC"][count - 1];
+    out.push_str(&format!("\\RightLabel{{\\scriptsize {}}}\n\\{}{{{}}}\n", label, inference, conclusion));
+}
+
+fn latex_judgement(step: &Step) -> String {
+    match step {
+        Step::Infer { expr, ty: Some(ty) } => {
+            format!("$\\Gamma \\vdash$ \\texttt{{{}}} $\\Rightarrow {}$", latex_text(expr), latex_ty(ty))
+        }
+        Step::Infer { expr, ty: None } => {
+            format!("$\\Gamma \\vdash$ \\texttt{{{}}} $\\Rightarrow\\ ?$", latex_text(expr))
+        }
+        Step::Check { expr, ty } => {
+            format!("$\\Gamma \\vdash$ \\texttt{{{}}} $\\Leftarrow {}$", latex_text(expr), latex_ty(ty))
+        }
+        Step::Unify { expected, found } => format!("${} \\sim {}$", latex_ty(expected), latex_ty(found)),
+        Step::Bind { var, ty } => format!("${} := {}$", latex_var(var), latex_ty(ty)),
+        Step::Instantiate { name, scheme, ty } => format!(
+            "\\texttt{{{}}} $: {} \\sqsubseteq {}$",
+            latex_text(name), latex_scheme(scheme), latex_ty(ty)
+        ),
+        Step::Generalize { name, scheme } => {
+            format!("\\texttt{{{}}} $: {}$", latex_text(name), latex_scheme(scheme))
+        }
+    }
+}
+
+/// Escape text for `\texttt`
+fn latex_text(s: &str) -> String {
+    let mut out = String::new();
+
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '{' | '}' | '_' | '#' | '&' | '%' | '$' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '^' => out.push_str("\\^{}"),
+            '~' => out.push_str("\\~{}"),
+            '<' => out.push_str("\\textless{}"),
+            '>' => out.push_str("\\textgreater{}"),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// A type variable in math mode: the number of a fresh variable or skolem becomes a subscript
+fn latex_var(var: &str) -> String {
+    let (name, number) = match var.split_once('#') {
+        Some((name, number)) => (name, number),
+        None => {
+            let digits = var.trim_start_matches(|c: char| !c.is_ascii_digit());
+            (&var[..var.len() - digits.len()], digits)
+        }
+    };
+
+    let name = name.replace('_', "\\_");
+    match (name.is_empty(), number.is_empty()) {
+        (_, true) => name,
+        (true, false) => number.to_string(),
+        (false, false) => format!("{}_{{{}}}", name, number),
+    }
+}
+
+/// A type in math mode, parenthesized as by `Display for Ty`
+fn latex_ty(ty: &Ty) -> String {
+    match ty {
+        Ty::Unit => "\\mathsf{unit}".to_string(),
+        Ty::Int => "\\mathsf{int}".to_string(),
+        Ty::Bool => "\\mathsf{bool}".to_string(),
+        Ty::String => "\\mathsf{string}".to_string(),
+        Ty::Mono(var) => latex_var(var),
+        Ty::Var(id) => latex_var(&format!("t{}", id)),
+        Ty::Arrow(left, right, _) => {
+            let left = match **left {
+                Ty::Arrow(..) | Ty::Forall(..) => format!("({})", latex_ty(left)),
+                _ => latex_ty(left),
+            };
+            let right = match **right {
+                Ty::Forall(..) => format!("({})", latex_ty(right)),
+                _ => latex_ty(right),
+            };
+            format!("{} \\to {}", left, right)
+        }
+        Ty::App(name, args) => {
+            let mut out = format!("\\mathsf{{{}}}", name.replace('_', "\\_"));
+
+            for arg in args {
+                match arg {
+                    Ty::Arrow(..) | Ty::Forall(..) => out.push_str(&format!("\\;({})", latex_ty(arg))),
+                    Ty::App(_, inner) if !inner.is_empty() => out.push_str(&format!("\\;({})", latex_ty(arg))),
+                    _ => out.push_str(&format!("\\;{}", latex_ty(arg))),
+                }
+            }
+
+            out
+        }
+        Ty::Forall(vars, body) => format!("\\forall {}.\\, {}", latex_vars(vars), latex_ty(body)),
+    }
+}
+
+fn latex_vars(vars: &[TypeVar]) -> String {
+    vars.iter().map(|var| latex_var(var)).collect::<Vec<_>>().join("\\, ")
+}
+
+fn latex_scheme(scheme: &Scheme) -> String {
+    let mut out = String::new();
+
+    if !scheme.vars.is_empty() {
+        out.push_str(&format!("\\forall {}.\\, ", latex_vars(&scheme.vars)));
+    }
+
+    if !scheme.constraints.is_empty() {
+        let constraints: Vec<String> = scheme.constraints.iter()
+            .map(|constraint| format!("\\mathsf{{{}}}\\; {}", constraint.class, latex_var(&constraint.type_var)))
+            .collect();
+        out.push_str(&format!("({}) \\Rightarrow ", constraints.join(", ")));
+    }
+
+    out.push_str(&latex_ty(&scheme.ty));
+    out
+}
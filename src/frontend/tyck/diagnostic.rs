@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Display;
+use crate::frontend::ast::{ASTAtom, ASTExpr, Span};
+use crate::frontend::name_resolution::ResolvedASTExpr;
+use crate::frontend::ty::{Scheme, Ty, TypeVar};
+use crate::util::pp::pretty_op;
+
+/// Why two types were required to be equal
+#[derive(Debug, Clone)]
+pub enum Origin {
+    /// The `index`-th argument (counting from 1) of a call to `callee`
+    Argument { index: usize, callee: String, span: Span },
+    /// The function position of the call at `span`
+    Callee(Span),
+    IfCondition(Span),
+    IfBranches(Span),
+    /// The value of a let binding against its annotation
+    Annotation { name: String, span: Span },
+    /// The value of an unannotated let binding against the uses of the name
+    Binding { name: String, span: Span },
+    /// A lambda parameter against its annotation
+    ParamAnnotation { name: String, span: Span },
+    /// An instance method against the class signature
+    Method { method: String, instance: String },
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Argument { index, callee, span } => {
+                write!(f, "argument {} of {} at {}", index, callee, span)
+            }
+            Origin::Callee(span) => write!(f, "the function called at {}", span),
+            Origin::IfCondition(span) => write!(f, "the condition of the if at {}", span),
+            Origin::IfBranches(span) => write!(f, "the branches of the if at {}", span),
+            Origin::Annotation { name, span } => write!(f, "the definition of {} at {}", name, span),
+            Origin::Binding { name, span } => write!(f, "the binding of {} at {}", name, span),
+            Origin::ParamAnnotation { name, span } => {
+                write!(f, "the annotation of parameter {} at {}", name, span)
+            }
+            Origin::Method { method, instance } => write!(f, "method {} of instance {}", method, instance),
+        }
+    }
+}
+
+/// The function at the head of a curried call, and how many arguments it is already applied to
+pub fn call_head(func: &ResolvedASTExpr) -> (String, usize) {
+    match func {
+        ASTExpr::Apply { func, .. } => {
+            let (callee, applied) = call_head(func);
+            (callee, applied + 1)
+        }
+        ASTExpr::Atom(ASTAtom::Var(ident), _, _) => (ident.name.clone(), 0),
+        ASTExpr::Atom(ASTAtom::Op(op), _, _) => (format!("({})", pretty_op(op)), 0),
+        _ => ("the function".to_string(), 0),
+    }
+}
+
+/// The source name of a skolem, e.g. `a` for `a#3`
+pub fn skolem_name(skolem: &TypeVar) -> &str {
+    skolem.split('#').next().unwrap_or(skolem)
+}
+
+/// Readable names for the type variables of a diagnostic: unification variables
+/// become `'a`, `'b`, ... in order of appearance, rigid ones keep their source name
+struct TyNames<'a> {
+    rigid: &'a HashMap<TypeVar, String>,
+    names: HashMap<TypeVar, String>,
+    // `'` for names that cannot be confused with source variables, empty for source-like ones
+    quote: &'static str,
+    // Index of the next name to try
+    next: usize,
+}
+
+impl<'a> TyNames<'a> {
+    fn new(rigid: &'a HashMap<TypeVar, String>, quote: &'static str) -> Self {
+        TyNames { rigid, names: HashMap::new(), quote, next: 0 }
+    }
+
+    fn name(&mut self, var: &TypeVar) -> String {
+        if self.rigid.contains_key(var) {
+            return skolem_name(var).to_string();
+        }
+
+        if let Some(name) = self.names.get(var) {
+            return name.clone();
+        }
+
+        let name = loop {
+            let letter = (b'a' + (self.next % 26) as u8) as char;
+            let name = match self.next / 26 {
+                0 => format!("{}{}", self.quote, letter),
+                n => format!("{}{}{}", self.quote, letter, n),
+            };
+            self.next += 1;
+
+            // Unquoted names must differ from the rigid variables
+            if !self.rigid.keys().any(|rigid| skolem_name(rigid) == name) {
+                break name;
+            }
+        };
+
+        self.names.insert(var.clone(), name.clone());
+        name
+    }
+}
+
+/// Renders the types of a diagnostic other than a mismatch, naming their variables consistently
+pub struct TyPrinter<'a> {
+    names: TyNames<'a>,
+}
+
+impl<'a> TyPrinter<'a> {
+    pub fn new(rigid: &'a HashMap<TypeVar, String>) -> Self {
+        TyPrinter { names: TyNames::new(rigid, "'") }
+    }
+
+    /// Names the unification variables `a`, `b`, ... like variables written in the source
+    pub fn source(rigid: &'a HashMap<TypeVar, String>) -> Self {
+        TyPrinter { names: TyNames::new(rigid, "") }
+    }
+
+    pub fn ty(&mut self, ty: &Ty) -> String {
+        let mut renderer = Renderer { names: &mut self.names, out: String::new(), mark: None };
+        renderer.write(ty, None);
+        renderer.out
+    }
+
+    pub fn scheme(&mut self, scheme: &Scheme) -> String {
+        let mut out = String::new();
+
+        if !scheme.vars.is_empty() {
+            let vars: Vec<String> = scheme.vars.iter().map(|var| self.names.name(var)).collect();
+            out.push_str(&format!("forall {}. ", vars.join(" ")));
+        }
+
+        if !scheme.constraints.is_empty() {
+            let constraints: Vec<String> = scheme.constraints.iter()
+                .map(|constraint| format!("{} {}", constraint.class, self.names.name(&constraint.type_var)))
+                .collect();
+            out.push_str(&format!("({}) => ", constraints.join(", ")));
+        }
+
+        out.push_str(&self.ty(&scheme.ty));
+        out
+    }
+}
+
+/// Renders a type, remembering where the subterm at `target` was written
+struct Renderer<'n, 'a> {
+    names: &'n mut TyNames<'a>,
+    out: String,
+    mark: Option<(usize, usize)>,
+}
+
+impl Renderer<'_, '_> {
+    fn write(&mut self, ty: &Ty, target: Option<&[usize]>) {
+        let start = self.out.len();
+
+        match ty {
+            Ty::Unit | Ty::Int | Ty::Bool | Ty::String => self.out.push_str(&ty.to_string()),
+            Ty::Mono(var) => {
+                let name = self.names.name(var);
+                self.out.push_str(&name);
+            }
+            Ty::Var(_) => {
+                let name = self.names.name(&ty.to_string());
+                self.out.push_str(&name);
+            }
+            Ty::Arrow(left, right, effect) => {
+                self.write_nested(left, matches!(**left, Ty::Arrow(..) | Ty::Forall(..)), child(target, 0));
+                if effect.is_pure() {
+                    self.out.push_str(" -> ");
+                } else {
+                    self.out.push_str(&format!(" -{{{}}}-> ", effect));
+                }
+                self.write_nested(right, matches!(**right, Ty::Forall(..)), child(target, 1));
+            }
+            Ty::App(name, args) => {
+                self.out.push_str(name);
+
+                for (i, arg) in args.iter().enumerate() {
+                    self.out.push(' ');
+
+                    let parens = match arg {
+                        Ty::Arrow(..) | Ty::Forall(..) => true,
+                        Ty::App(_, inner) => !inner.is_empty(),
+                        _ => false,
+                    };
+                    self.write_nested(arg, parens, child(target, i));
+                }
+            }
+            Ty::Forall(vars, body) => {
+                // The binders are named like their occurrences in the body
+                let binders: Vec<String> = vars.iter().map(|var| self.names.name(var)).collect();
+                self.out.push_str(&format!("forall {}. ", binders.join(" ")));
+                self.write(body, child(target, 0));
+            }
+        }
+
+        if target.is_some_and(|path| path.is_empty()) {
+            self.mark = Some((start, self.out.len()));
+        }
+    }
+
+    fn write_nested(&mut self, ty: &Ty, parens: bool, target: Option<&[usize]>) {
+        if parens {
+            self.out.push('(');
+            self.write(ty, target);
+            self.out.push(')');
+        } else {
+            self.write(ty, target);
+        }
+    }
+}
+
+/// The path below the `index`-th child, if `path` goes through it
+fn child(path: Option<&[usize]>, index: usize) -> Option<&[usize]> {
+    match path?.split_first() {
+        Some((head, rest)) if *head == index => Some(rest),
+        _ => None,
+    }
+}
+
+/// Path to the outermost subterm where `expected` and `found` cannot agree
+fn divergence(expected: &Ty, found: &Ty, rigid: &HashMap<TypeVar, String>) -> Option<Vec<usize>> {
+    let within = |index: usize, path: Option<Vec<usize>>| {
+        path.map(|mut path| {
+            path.insert(0, index);
+            path
+        })
+    };
+
+    match (expected, found) {
+        (Ty::Mono(a), Ty::Mono(b)) if a == b => None,
+        // A unification variable could still become anything
+        (Ty::Var(_), _) | (_, Ty::Var(_)) => None,
+        (Ty::Mono(a), _) if !rigid.contains_key(a) => None,
+        (_, Ty::Mono(b)) if !rigid.contains_key(b) => None,
+        (Ty::Unit, Ty::Unit) | (Ty::Int, Ty::Int) | (Ty::Bool, Ty::Bool) | (Ty::String, Ty::String) => None,
+        (Ty::Arrow(e1, e2, _), Ty::Arrow(f1, f2, _)) => {
+            within(0, divergence(e1, f1, rigid)).or_else(|| within(1, divergence(e2, f2, rigid)))
+        }
+        (Ty::App(n1, es), Ty::App(n2, fs)) if n1 == n2 && es.len() == fs.len() => {
+            es.iter().zip(fs).enumerate()
+                .find_map(|(i, (e, f))| within(i, divergence(e, f, rigid)))
+        }
+        (Ty::Forall(v1, e), Ty::Forall(v2, f)) if v1.len() == v2.len() => {
+            within(0, divergence(e, f, rigid))
+        }
+        _ => Some(vec![]),
+    }
+}
+
+/// Describe a failed unification of `expected` with `found`. The subterms where the
+/// types diverge are underlined; `reason` explains failures without such a subterm.
+pub fn mismatch(
+    origin: Option<&Origin>,
+    expected: &Ty,
+    found: &Ty,
+    rigid: &HashMap<TypeVar, String>,
+    reason: &str,
+) -> String {
+    let path = divergence(expected, found, rigid);
+    let mut names = TyNames::new(rigid, "'");
+
+    let mut lines = vec![match origin {
+        Some(origin) => format!("Type mismatch in {}:", origin),
+        None => "Type mismatch:".to_string(),
+    }];
+
+    let mut notes = Vec::new();
+
+    for (label, ty) in [("expected", expected), ("found", found)] {
+        let mut renderer = Renderer { names: &mut names, out: String::new(), mark: None };
+        renderer.write(ty, path.as_deref());
+
+        let prefix = format!("  {:<10}", format!("{}:", label));
+        lines.push(format!("{}{}", prefix, renderer.out));
+
+        if let Some((start, end)) = renderer.mark {
+            lines.push(format!("{}{}{}", " ".repeat(prefix.len()), " ".repeat(start), "^".repeat(end - start)));
+        }
+
+        // Mention where a diverging rigid variable comes from
+        let diverging = path.as_ref().and_then(|path| subterm(ty, path));
+        if let Some(Ty::Mono(var)) = diverging && let Some(binder) = rigid.get(var) {
+            notes.push(format!("  {} is a rigid type variable, bound by {}", skolem_name(var), binder));
+        }
+    }
+
+    if path.is_none() {
+        // The types only disagree through a variable occurring in its own solution
+        let infinite = match (expected, found) {
+            (var @ (Ty::Mono(_) | Ty::Var(_)), ty) | (ty, var @ (Ty::Mono(_) | Ty::Var(_)))
+                if ty != var && mentions(ty, var) => Some((var, ty)),
+            _ => None,
+        };
+
+        match infinite {
+            Some((var, ty)) => {
+                let mut renderer = Renderer { names: &mut names, out: String::new(), mark: None };
+                renderer.write(var, None);
+                let var = std::mem::take(&mut renderer.out);
+                renderer.write(ty, None);
+
+                notes.push(format!(
+                    "  {} occurs in {}, so the type would be infinite",
+                    var, renderer.out
+                ));
+            }
+            None => notes.push(format!("  {}", reason)),
+        }
+    }
+
+    lines.extend(notes);
+    lines.join("\n")
+}
+
+/// Whether the type variable `var` occurs in `ty`
+fn mentions(ty: &Ty, var: &Ty) -> bool {
+    match ty {
+        Ty::Arrow(left, right, _) => mentions(left, var) || mentions(right, var),
+        Ty::App(_, args) => args.iter().any(|arg| mentions(arg, var)),
+        Ty::Forall(bound, body) => !matches!(var, Ty::Mono(v) if bound.contains(v)) && mentions(body, var),
+        _ => ty == var,
+    }
+}
+
+fn subterm<'t>(ty: &'t Ty, path: &[usize]) -> Option<&'t Ty> {
+    let Some((head, rest)) = path.split_first() else {
+        return Some(ty);
+    };
+
+    match ty {
+        Ty::Arrow(left, _, _) if *head == 0 => subterm(left, rest),
+        Ty::Arrow(_, right, _) if *head == 1 => subterm(right, rest),
+        Ty::App(_, args) => subterm(args.get(*head)?, rest),
+        Ty::Forall(_, body) => subterm(body, rest),
+        _ => None,
+    }
+}
@@ -0,0 +1,129 @@
+use crate::frontend::ast::{ASTAtom, ASTExpr};
+use crate::frontend::name_resolution::ResolvedIdent;
+use crate::frontend::ty::{boxed_ty, Ty, TypeVar};
+use crate::frontend::tyck::tyck::TypedASTExpr;
+
+/// The typed program in the style of System F: every polymorphic binding abstracts over
+/// its type variables, and every use of one applies it to the types it is used at
+#[derive(Debug, Clone)]
+pub enum ElabExpr {
+    Atom {
+        atom: ASTAtom<ResolvedIdent>,
+        ty: Ty,
+    },
+    /// `x [τ1, ..., τn]`: a polymorphic binding instantiated with one type per parameter
+    TyApp {
+        var: ResolvedIdent,
+        ty_args: Vec<Ty>,
+        ty: Ty,
+    },
+    If {
+        cond: Box<ElabExpr>,
+        then: Box<ElabExpr>,
+        else_: Box<ElabExpr>,
+        ty: Ty,
+    },
+    Let {
+        bind: ResolvedIdent,
+        value: Box<ElabExpr>,
+        body: Box<ElabExpr>,
+        ty: Ty,
+    },
+    /// `Λ a b. e`, only as the value of a let
+    TyAbs {
+        params: Vec<TypeVar>,
+        body: Box<ElabExpr>,
+    },
+    Apply {
+        func: Box<ElabExpr>,
+        arg: Box<ElabExpr>,
+        ty: Ty,
+    },
+    Lambda {
+        arg: (ResolvedIdent, Ty),
+        body: Box<ElabExpr>,
+        ret_ty: Ty,
+    },
+}
+
+/// Make the polymorphism of a typed program explicit. The type checker records the
+/// type variables each polymorphic let abstracts over on the binding, and the types
+/// each use of a polymorphic variable instantiates them to on the atom.
+pub fn elaborate(expr: TypedASTExpr) -> ElabExpr {
+    Elaborator { ty_params: Vec::new() }.expr(expr)
+}
+
+struct Elaborator {
+    // The variables of the enclosing type abstractions
+    ty_params: Vec<TypeVar>,
+}
+
+impl Elaborator {
+    fn expr(&mut self, expr: TypedASTExpr) -> ElabExpr {
+        match expr {
+            ASTExpr::Atom(ASTAtom::Var(var), ty, ty_args) if !ty_args.is_empty() => ElabExpr::TyApp {
+                var,
+                ty_args: ty_args.into_iter().map(|arg| self.erase(arg, &[])).collect(),
+                ty: self.erase(ty, &[]),
+            },
+            ASTExpr::Atom(atom, ty, _) => ElabExpr::Atom { atom, ty: self.erase(ty, &[]) },
+            ASTExpr::If { cond, then, else_, ty, .. } => ElabExpr::If {
+                cond: Box::new(self.expr(*cond)),
+                then: Box::new(self.expr(*then)),
+                else_: Box::new(self.expr(*else_)),
+                ty: self.erase(ty, &[]),
+            },
+            ASTExpr::Let { bind: (ident, _), value, body, ty, ty_params, .. } if !ty_params.is_empty() => {
+                let depth = self.ty_params.len();
+                self.ty_params.extend(ty_params.iter().cloned());
+                let value = self.expr(*value);
+                self.ty_params.truncate(depth);
+                let body = self.expr(*body);
+
+                ElabExpr::Let {
+                    bind: ident,
+                    value: Box::new(ElabExpr::TyAbs { params: ty_params, body: Box::new(value) }),
+                    body: Box::new(body),
+                    ty: self.erase(ty, &[]),
+                }
+            }
+            ASTExpr::Let { bind: (ident, _), value, body, ty, .. } => ElabExpr::Let {
+                bind: ident,
+                value: Box::new(self.expr(*value)),
+                body: Box::new(self.expr(*body)),
+                ty: self.erase(ty, &[]),
+            },
+            ASTExpr::Apply { func, args, ty, .. } => ElabExpr::Apply {
+                func: Box::new(self.expr(*func)),
+                arg: Box::new(self.expr(*args)),
+                ty: self.erase(ty, &[]),
+            },
+            ASTExpr::Lambda { arg: (ident, arg_ty), body, ret_ty, .. } => ElabExpr::Lambda {
+                arg: (ident, self.erase(arg_ty, &[])),
+                body: Box::new(self.expr(*body)),
+                ret_ty: self.erase(ret_ty, &[]),
+            },
+        }
+    }
+
+    /// `ty` with the type variables no type abstraction binds erased to the boxed
+    /// representation. They are left undetermined by the program, e.g. `t` in
+    /// `(fun x -> 1) (fun y -> y)`, where `y : t`, so what they stand for cannot
+    /// change its result. Those of `bound` are quantified within `ty`.
+    fn erase(&self, ty: Ty, bound: &[TypeVar]) -> Ty {
+        match ty {
+            Ty::Mono(var) if !bound.contains(&var) && !self.ty_params.contains(&var) => boxed_ty(),
+            Ty::Arrow(a, b, effect) => Ty::Arrow(
+                Box::new(self.erase(*a, bound)),
+                Box::new(self.erase(*b, bound)),
+                effect,
+            ),
+            Ty::App(name, args) => Ty::App(name, args.into_iter().map(|arg| self.erase(arg, bound)).collect()),
+            Ty::Forall(vars, body) => {
+                let bound = [bound, &vars].concat();
+                Ty::Forall(vars, Box::new(self.erase(*body, &bound)))
+            }
+            ty => ty,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fmt::Display;
+use crate::frontend::ast::TypeDecl;
+use crate::frontend::FrontendError;
+use crate::frontend::ty::Ty;
+
+/// The kind of a type: `*` for the types of values, `* -> *` for `List`, ...
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Kind {
+    Star,
+    Arrow(Box<Kind>, Box<Kind>),
+}
+
+impl Kind {
+    /// The kind of a constructor taking `arity` types
+    fn of_arity(arity: usize) -> Kind {
+        (0..arity).fold(Kind::Star, |kind, _| Kind::Arrow(Box::new(Kind::Star), Box::new(kind)))
+    }
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Star => write!(f, "*"),
+            Kind::Arrow(left, right) => match **left {
+                Kind::Arrow(..) => write!(f, "({}) -> {}", left, right),
+                Kind::Star => write!(f, "{} -> {}", left, right),
+            },
+        }
+    }
+}
+
+/// Kinds of the declared type constructors
+#[derive(Debug, Default)]
+pub struct KindEnv {
+    constructors: HashMap<String, Kind>,
+}
+
+impl KindEnv {
+    pub fn new() -> Self {
+        KindEnv::default()
+    }
+
+    pub fn declare(&mut self, decl: &TypeDecl) -> Result<(), FrontendError> {
+        if self.constructors.contains_key(&decl.name) {
+            return Err(FrontendError::TypeError(format!("Type {} is declared twice", decl.name)));
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(param) = decl.params.iter().find(|param| !seen.insert(*param)) {
+            return Err(FrontendError::TypeError(format!(
+                "Type parameter {} of {} is declared twice",
+                param, decl.name
+            )));
+        }
+
+        self.constructors.insert(decl.name.clone(), Kind::of_arity(decl.params.len()));
+        Ok(())
+    }
+
+    /// Infer the kind of `ty`. Type variables always stand for types of kind `*`.
+    pub fn kind_of(&self, ty: &Ty) -> Result<Kind, FrontendError> {
+        match ty {
+            Ty::Unit | Ty::Int | Ty::Bool | Ty::String | Ty::Mono(_) | Ty::Var(_) => Ok(Kind::Star),
+            Ty::Arrow(left, right, _) => {
+                self.check(left)?;
+                self.check(right)?;
+                Ok(Kind::Star)
+            }
+            Ty::Forall(_, body) => {
+                self.check(body)?;
+                Ok(Kind::Star)
+            }
+            Ty::App(name, args) => {
+                let mut kind = self.constructors.get(name).cloned().ok_or_else(|| {
+                    FrontendError::TypeError(format!("Unknown type {}", name))
+                })?;
+
+                for arg in args {
+                    self.check(arg)?;
+
+                    kind = match kind {
+                        Kind::Arrow(_, result) => *result,
+                        Kind::Star => return Err(FrontendError::TypeError(format!(
+                            "Type {} is applied to too many arguments in {}",
+                            name, ty
+                        ))),
+                    };
+                }
+
+                Ok(kind)
+            }
+        }
+    }
+
+    /// Check that `ty` is the type of values, i.e. has kind `*`
+    pub fn check(&self, ty: &Ty) -> Result<(), FrontendError> {
+        match self.kind_of(ty)? {
+            Kind::Star => Ok(()),
+            kind => Err(FrontendError::TypeError(format!(
+                "Type {} is missing arguments: it has kind {}, expected *",
+                ty, kind
+            ))),
+        }
+    }
+}
@@ -1,3 +1,10 @@
+pub mod common;
+pub mod derivation;
+pub mod diagnostic;
+pub mod elaborate;
+pub mod kind;
 pub mod type_class;
 pub mod tyck;
-mod subst;
\ No newline at end of file
+mod slice;
+pub(crate) mod subst;
+pub mod union_find;
\ No newline at end of file
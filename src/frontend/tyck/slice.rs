@@ -0,0 +1,328 @@
+use std::collections::{HashMap, HashSet};
+use crate::frontend::ast::{ASTAtom, ASTExpr, OpType};
+use crate::frontend::name_resolution::{NameIdentifier, ResolvedASTExpr};
+use crate::frontend::ty::{Effect, Scheme, Ty, TypeVar};
+use crate::frontend::tyck::diagnostic::{call_head, Origin};
+use crate::frontend::tyck::subst::apply_subst;
+use crate::frontend::tyck::union_find::UnionFind;
+
+/// An equality between two types, required by the program at `site`
+struct Constraint {
+    expected: Ty,
+    found: Ty,
+    site: usize,
+}
+
+/// How a variable is typed at its uses
+#[derive(Clone)]
+enum Binding<'e> {
+    Mono(Ty),
+    Poly(Scheme),
+    /// An unannotated function, which the type checker generalizes:
+    /// each use gets its own copy of the constraints of the definition
+    Inline(&'e ResolvedASTExpr, Env<'e>),
+}
+
+type Env<'e> = HashMap<NameIdentifier, Binding<'e>>;
+
+/// Collects the constraints of a program without solving them, so that
+/// the locations responsible for a type error can be searched for
+#[derive(Default)]
+pub struct Slicer {
+    constraints: Vec<Constraint>,
+    sites: Vec<Origin>,
+    site_ids: HashMap<String, usize>,
+    // The type variables of the constraints, solved afresh for each subset tried
+    vars: UnionFind,
+    rigid: HashSet<TypeVar>,
+    scoped: HashMap<TypeVar, TypeVar>,
+    fresh: usize,
+}
+
+impl Slicer {
+    /// A minimal set of locations of `expr` whose constraints cannot hold together,
+    /// or `None` if the constraints of `expr` are satisfiable. `globals` types the
+    /// names bound outside of `expr`, i.e. class methods.
+    pub fn slice(expr: &ResolvedASTExpr, globals: &HashMap<NameIdentifier, Scheme>) -> Option<Vec<Origin>> {
+        let mut slicer = Slicer::default();
+        slicer.generate(expr, &Self::global_env(globals));
+        slicer.minimize()
+    }
+
+    /// Like `slice`, for the `body` of an instance method checked against its
+    /// `expected` type, which is required at `origin`
+    pub fn slice_method(
+        body: &ResolvedASTExpr,
+        expected: &Ty,
+        origin: Origin,
+        globals: &HashMap<NameIdentifier, Scheme>,
+    ) -> Option<Vec<Origin>> {
+        let mut slicer = Slicer::default();
+        let found = slicer.generate(body, &Self::global_env(globals));
+
+        // Variables of the signature other than the class parameter are left flexible, as in the type checker
+        let vars: Vec<TypeVar> = expected.free_vars().into_iter().collect();
+        let expected = slicer.instantiate(&vars, expected);
+
+        slicer.require(expected, found, origin);
+        slicer.minimize()
+    }
+
+    fn global_env<'e>(globals: &HashMap<NameIdentifier, Scheme>) -> Env<'e> {
+        globals.iter()
+            .map(|(id, scheme)| (id.clone(), Binding::Poly(scheme.clone())))
+            .collect()
+    }
+
+    /// The sites of a minimal unsatisfiable subset of the constraints, if there is one
+    fn minimize(&self) -> Option<Vec<Origin>> {
+        let mut kept: Vec<usize> = (0..self.sites.len()).collect();
+        if self.solvable(&kept) {
+            return None;
+        }
+
+        // Deletion filter: a site is dropped for good if the error remains without it
+        for site in 0..self.sites.len() {
+            let without: Vec<usize> = kept.iter().copied().filter(|kept| *kept != site).collect();
+
+            if !self.solvable(&without) {
+                kept = without;
+            }
+        }
+
+        Some(kept.into_iter().map(|site| self.sites[site].clone()).collect())
+    }
+
+    fn fresh_ty(&mut self) -> Ty {
+        Ty::Var(self.vars.fresh())
+    }
+
+    /// Replace the quantified variables of a scheme with fresh type variables
+    fn instantiate(&mut self, vars: &[TypeVar], ty: &Ty) -> Ty {
+        let subst: HashMap<TypeVar, Ty> = vars.iter()
+            .map(|var| (var.clone(), self.fresh_ty()))
+            .collect();
+
+        apply_subst(ty.clone(), &subst)
+    }
+
+    /// Replace the quantified variables of an annotation with rigid ones
+    fn skolemize(&mut self, vars: &[TypeVar], ty: &Ty) -> (Vec<TypeVar>, Ty) {
+        let skolems: Vec<TypeVar> = vars.iter().map(|var| {
+            let skolem = format!("{}#s{}", var, self.fresh);
+            self.fresh += 1;
+            self.rigid.insert(skolem.clone());
+            skolem
+        }).collect();
+
+        let subst: HashMap<TypeVar, Ty> = vars.iter().cloned()
+            .zip(skolems.iter().map(|skolem| Ty::Mono(skolem.clone())))
+            .collect();
+
+        (skolems, apply_subst(ty.clone(), &subst))
+    }
+
+    /// Type variables bound by enclosing annotations stand for their skolems
+    fn scoped_ty(&self, ty: &Ty, bound: &[TypeVar]) -> Ty {
+        let subst: HashMap<TypeVar, Ty> = self.scoped.iter()
+            .filter(|(var, _)| !bound.contains(var))
+            .map(|(var, skolem)| (var.clone(), Ty::Mono(skolem.clone())))
+            .collect();
+
+        apply_subst(ty.clone(), &subst)
+    }
+
+    fn require(&mut self, expected: Ty, found: Ty, origin: Origin) {
+        // Copies of an inlined definition share the sites of the original
+        let next = self.sites.len();
+        let site = *self.site_ids.entry(origin.to_string()).or_insert(next);
+        if site == next {
+            self.sites.push(origin);
+        }
+
+        self.constraints.push(Constraint { expected, found, site });
+    }
+
+    fn generate<'e>(&mut self, expr: &'e ResolvedASTExpr, env: &Env<'e>) -> Ty {
+        match expr {
+            ASTExpr::Atom(atom, _, _) => match atom {
+                ASTAtom::Int(_) => Ty::Int,
+                ASTAtom::Bool(_) => Ty::Bool,
+                ASTAtom::Str(_) => Ty::String,
+                ASTAtom::Unit => Ty::Unit,
+                ASTAtom::Hole(..) => self.fresh_ty(),
+                ASTAtom::Op(OpType::Add | OpType::Sub | OpType::Mul | OpType::Div) => Ty::Arrow(
+                    Box::new(Ty::Int),
+                    Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int), Effect::PURE)),
+                    Effect::PURE,
+                ),
+                ASTAtom::Op(_) => {
+                    let operand = self.fresh_ty();
+                    Ty::Arrow(
+                        Box::new(operand.clone()),
+                        Box::new(Ty::Arrow(Box::new(operand), Box::new(Ty::Bool), Effect::PURE)),
+                        Effect::PURE,
+                    )
+                }
+                ASTAtom::Var(ident) => match env.get(&ident.id).cloned() {
+                    Some(Binding::Mono(ty)) => ty,
+                    Some(Binding::Poly(scheme)) => self.instantiate(&scheme.vars, &scheme.ty),
+                    Some(Binding::Inline(value, def_env)) => self.generate(value, &def_env),
+                    None => self.fresh_ty(),
+                },
+            },
+            ASTExpr::If { cond, then, else_, span, .. } => {
+                let cond_ty = self.generate(cond, env);
+                self.require(Ty::Bool, cond_ty, Origin::IfCondition(*span));
+
+                let then_ty = self.generate(then, env);
+                let else_ty = self.generate(else_, env);
+                self.require(then_ty.clone(), else_ty, Origin::IfBranches(*span));
+
+                then_ty
+            }
+            ASTExpr::Let { bind: (ident, None), value, body, span, .. } => {
+                let mut body_env = env.clone();
+
+                let value_ty = self.generate(value, env);
+                let binding = if matches!(**value, ASTExpr::Lambda { .. }) {
+                    Binding::Inline(value, env.clone())
+                } else {
+                    // Removing the binding site detaches the name from its value
+                    let bound = self.fresh_ty();
+                    self.require(bound.clone(), value_ty, Origin::Binding { name: ident.name.clone(), span: *span });
+                    Binding::Mono(bound)
+                };
+
+                body_env.insert(ident.id.clone(), binding);
+                self.generate(body, &body_env)
+            }
+            ASTExpr::Let { bind: (ident, Some(scheme)), value, body, span, .. } => {
+                let scheme = Scheme {
+                    ty: self.scoped_ty(&scheme.ty, &scheme.vars),
+                    ..scheme.clone()
+                };
+
+                let mut body_env = env.clone();
+                body_env.insert(ident.id.clone(), Binding::Poly(scheme.clone()));
+
+                // The definition is checked against rigid variables, as in the type checker
+                let (skolems, skolem_ty) = self.skolemize(&scheme.vars, &scheme.ty);
+                let outer_scope = self.scoped.clone();
+                self.scoped.extend(scheme.vars.iter().cloned().zip(skolems));
+
+                let value_ty = self.generate(value, &body_env);
+                self.scoped = outer_scope;
+
+                self.require(skolem_ty, value_ty, Origin::Annotation { name: ident.name.clone(), span: *span });
+                self.generate(body, &body_env)
+            }
+            ASTExpr::Apply { func, args, span, .. } => {
+                let func_ty = self.generate(func, env);
+                let arg_ty = self.generate(args, env);
+                let ret_ty = self.fresh_ty();
+
+                let (callee, applied) = call_head(func);
+                self.require(
+                    Ty::Arrow(Box::new(arg_ty), Box::new(ret_ty.clone()), Effect::PURE),
+                    func_ty,
+                    Origin::Argument { index: applied + 1, callee, span: *span },
+                );
+
+                ret_ty
+            }
+            ASTExpr::Lambda { arg: (ident, annotation), body, span, .. } => {
+                let arg_ty = self.fresh_ty();
+
+                if let Some(annotation) = annotation {
+                    let annotation = self.scoped_ty(annotation, &[]);
+                    self.require(annotation, arg_ty.clone(), Origin::ParamAnnotation { name: ident.name.clone(), span: *span });
+                }
+
+                let mut body_env = env.clone();
+                body_env.insert(ident.id.clone(), Binding::Mono(arg_ty.clone()));
+
+                let body_ty = self.generate(body, &body_env);
+                Ty::Arrow(Box::new(arg_ty), Box::new(body_ty), Effect::PURE)
+            }
+        }
+    }
+
+    fn solvable(&self, sites: &[usize]) -> bool {
+        let mut solver = Solver {
+            subst: self.vars.clone(),
+            rigid: self.rigid.clone(),
+            fresh: 0,
+        };
+
+        self.constraints.iter()
+            .filter(|constraint| sites.contains(&constraint.site))
+            .all(|constraint| solver.unify(constraint.expected.clone(), constraint.found.clone()))
+    }
+}
+
+/// First-order unification, with rigid variables that only unify with themselves
+struct Solver {
+    subst: UnionFind,
+    rigid: HashSet<TypeVar>,
+    fresh: usize,
+}
+
+impl Solver {
+    fn is_flexible(&self, ty: &Ty) -> bool {
+        match ty {
+            Ty::Var(_) => true,
+            Ty::Mono(var) => !self.rigid.contains(var),
+            _ => false,
+        }
+    }
+
+    /// Solve the flexible variable `var` to `ty`, failing if `var` occurs in it
+    fn solve(&mut self, var: &Ty, ty: Ty) -> bool {
+        for ty in [var, &ty] {
+            if let Ty::Mono(name) = ty && !self.rigid.contains(name) {
+                self.subst.declare(name);
+            }
+        }
+
+        let Some(root) = self.subst.class(var) else {
+            return false;
+        };
+
+        if self.subst.class(&ty) == Some(root) {
+            return true;
+        }
+
+        if self.subst.occurs(root, &ty) {
+            return false;
+        }
+
+        self.subst.bind(root, ty);
+        true
+    }
+
+    fn unify(&mut self, a: Ty, b: Ty) -> bool {
+        match (self.subst.apply(a), self.subst.apply(b)) {
+            (Ty::Unit, Ty::Unit) | (Ty::Int, Ty::Int) | (Ty::Bool, Ty::Bool) | (Ty::String, Ty::String) => true,
+            (Ty::Mono(x), Ty::Mono(y)) if x == y => true,
+            (var, ty) | (ty, var) if self.is_flexible(&var) => self.solve(&var, ty),
+            (Ty::Arrow(a1, a2, _), Ty::Arrow(b1, b2, _)) => self.unify(*a1, *b1) && self.unify(*a2, *b2),
+            (Ty::App(n1, args1), Ty::App(n2, args2)) if n1 == n2 && args1.len() == args2.len() => {
+                args1.into_iter().zip(args2).all(|(a, b)| self.unify(a, b))
+            }
+            (Ty::Forall(v1, b1), Ty::Forall(v2, b2)) if v1.len() == v2.len() => {
+                let skolems: Vec<Ty> = v1.iter().map(|_| {
+                    let skolem = format!("#{}", self.fresh);
+                    self.fresh += 1;
+                    self.rigid.insert(skolem.clone());
+                    Ty::Mono(skolem)
+                }).collect();
+
+                let b1 = apply_subst(*b1, &v1.into_iter().zip(skolems.clone()).collect());
+                let b2 = apply_subst(*b2, &v2.into_iter().zip(skolems).collect());
+                self.unify(b1, b2)
+            }
+            _ => false,
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use crate::frontend::ty::{Ty, TypeVar};
 
-pub fn apply_subst(ty: Ty, subst: HashMap<TypeVar, Ty>) -> Ty {
+pub fn apply_subst(ty: Ty, subst: &HashMap<TypeVar, Ty>) -> Ty {
     match ty {
         Ty::Mono(var) => {
             if let Some(substituted_ty) = subst.get(&var) {
@@ -10,12 +10,24 @@ pub fn apply_subst(ty: Ty, subst: HashMap<TypeVar, Ty>) -> Ty {
                 Ty::Mono(var)
             }
         }
-        Ty::Arrow(left, right) => {
+        Ty::Arrow(left, right, effect) => {
             Ty::Arrow(
-                Box::new(apply_subst(*left, subst.clone())),
+                Box::new(apply_subst(*left, subst)),
                 Box::new(apply_subst(*right, subst)),
+                effect,
             )
         }
+        Ty::App(name, args) => Ty::App(
+            name,
+            args.into_iter().map(|arg| apply_subst(arg, subst)).collect(),
+        ),
+        Ty::Forall(vars, body) => {
+            // Bound variables are not substituted
+            let mut subst = subst.clone();
+            subst.retain(|var, _| !vars.contains(var));
+
+            Ty::Forall(vars, Box::new(apply_subst(*body, &subst)))
+        }
         _ => ty,
     }
 }
\ No newline at end of file
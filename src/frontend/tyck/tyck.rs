@@ -1,67 +1,250 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Display;
-use crate::frontend::ast::{ASTAtom, ASTExpr, OpType};
+use crate::frontend::ast::{ASTAtom, ASTExpr, Decl, OpType, Span};
 use crate::frontend::FrontendError;
-use crate::frontend::name_resolution::{ResolvedASTExpr, ResolvedIdent};
-use crate::frontend::ty::{Scheme, Ty, TypeVar, TypingContext};
+use crate::frontend::name_resolution::{NameIdentifier, ResolvedASTExpr, ResolvedIdent, ResolvedProgram, UnboundName};
+use crate::frontend::ty::{Effect, Scheme, Ty, TypeVar, TypingContext};
+use crate::frontend::tyck::common::{Inference, MethodImpl};
+use crate::frontend::tyck::derivation::{Derivations, Recorder, Step};
+use crate::frontend::tyck::diagnostic::{call_head, mismatch, Origin, TyPrinter};
+use crate::frontend::tyck::kind::KindEnv;
+use crate::frontend::tyck::slice::Slicer;
 use crate::frontend::tyck::subst::apply_subst;
+use crate::frontend::tyck::union_find::UnionFind;
+use crate::frontend::tyck::type_class::{ClassEnv, MethodTable, TypeClassConstraint, Wanted, EQ_CLASS, ORD_CLASS};
+use crate::util::pp::pretty_source;
 
 // Fully-typed AST expression
 pub type TypedASTExpr = ASTExpr<ResolvedIdent, Ty>;
 
+/// `==` and `!=` have type `forall a. (Eq a) => a -> a -> Bool`,
+/// the ordering operators require `Ord a` instead
+pub fn comparison_scheme(op: &OpType) -> Scheme {
+    let class = match op {
+        OpType::Eq | OpType::Neq => EQ_CLASS,
+        _ => ORD_CLASS,
+    };
+
+    let operand = Ty::Mono("a".to_string());
+
+    Scheme {
+        vars: vec!["a".to_string()],
+        constraints: vec![TypeClassConstraint {
+            class: class.to_string(),
+            type_var: "a".to_string(),
+        }],
+        ty: Ty::Arrow(
+            Box::new(operand.clone()),
+            Box::new(Ty::Arrow(Box::new(operand), Box::new(Ty::Bool), Effect::PURE)),
+            Effect::PURE,
+        ),
+    }
+}
+
+/// Strip the `Option`s of an expression whose every node has been typed
+pub fn unwrap_ast_expr(expr: ResolvedASTExpr) -> TypedASTExpr {
+    match expr {
+        ResolvedASTExpr::Atom(atom, ty, ty_args) => ASTExpr::Atom(atom, ty.unwrap(), ty_args),
+        ResolvedASTExpr::If { cond, then, else_, ty, span } => ASTExpr::If {
+            cond: Box::new(unwrap_ast_expr(*cond)),
+            then: Box::new(unwrap_ast_expr(*then)),
+            else_: Box::new(unwrap_ast_expr(*else_)),
+            ty: ty.unwrap(),
+            span,
+        },
+        ResolvedASTExpr::Let { bind, value, body, ty, ty_params, span } => ASTExpr::Let {
+            bind,
+            value: Box::new(unwrap_ast_expr(*value)),
+            body: Box::new(unwrap_ast_expr(*body)),
+            ty: ty.unwrap(),
+            ty_params,
+            span,
+        },
+        ResolvedASTExpr::Apply { func, args, ty, span } => ASTExpr::Apply {
+            func: Box::new(unwrap_ast_expr(*func)),
+            args: Box::new(unwrap_ast_expr(*args)),
+            ty: ty.unwrap(),
+            span,
+        },
+        ResolvedASTExpr::Lambda { arg: (ident, arg_ty), body, ret_ty, span } => ASTExpr::Lambda {
+            arg: (ident, arg_ty.unwrap()),
+            body: Box::new(unwrap_ast_expr(*body)),
+            ret_ty: ret_ty.unwrap(),
+            span,
+        },
+    }
+}
+
+/// An expression as written, for derivations
+fn source(expr: &ResolvedASTExpr) -> String {
+    pretty_source(expr, &|ident: &ResolvedIdent| ident.name.clone())
+}
+
+/// A typed hole met while checking, with the bindings in scope at that point
+#[derive(Debug)]
+struct Hole {
+    name: Option<String>,
+    span: Span,
+    ty: Ty,
+    bindings: Vec<(String, Scheme)>,
+}
+
 #[derive(Debug)]
 pub struct TypeChecker {
     context: TypingContext,
-    fresh: usize,
-    subst: HashMap<TypeVar, Ty>,
+    subst: UnionFind,
+    classes: ClassEnv,
+    kinds: KindEnv,
+    // Class constraints arising from uses of constrained bindings
+    wanted: Vec<Wanted>,
+    // Class constraints assumed while checking annotated bindings
+    given: Vec<TypeClassConstraint>,
+    // Skolem constants introduced when checking against a polymorphic type,
+    // which only unify with themselves, and what bound each of them
+    rigid: HashMap<TypeVar, String>,
+    // Why the expression being checked must have the expected type, innermost last
+    origins: Vec<Origin>,
+    // Type variables of the enclosing annotations, mapped to their skolems
+    scoped: HashMap<TypeVar, TypeVar>,
+    // Source names of the bound identifiers and the order they were bound in, for diagnostics
+    names: HashMap<NameIdentifier, (String, usize)>,
+    holes: Vec<Hole>,
+    // Report every location involved in a type error, not only where it was found
+    slice_errors: bool,
+    // Judgements and unifications as derivation trees, only when requested
+    derivation: Option<Recorder>,
 }
 
 impl TypeChecker {
     pub fn new() -> Self {
         TypeChecker {
             context: TypingContext::new(),
-            fresh: 0,
-            subst: HashMap::new(),
+            subst: UnionFind::new(),
+            classes: ClassEnv::new(),
+            kinds: KindEnv::new(),
+            wanted: Vec::new(),
+            given: Vec::new(),
+            rigid: HashMap::new(),
+            origins: Vec::new(),
+            scoped: HashMap::new(),
+            names: HashMap::new(),
+            holes: Vec::new(),
+            slice_errors: false,
+            derivation: None,
         }
     }
 
-    fn fresh_ty(&mut self) -> Ty {
-        let v = format!("t{}", self.fresh);
-        self.fresh += 1;
-        Ty::Mono(v)
+    /// On a type error, also search for a minimal set of locations causing it
+    pub fn with_error_slicing(enabled: bool) -> Self {
+        TypeChecker {
+            slice_errors: enabled,
+            ..Self::new()
+        }
     }
 
-    fn apply_subst(&self, ty: Ty) -> Ty {
-        match ty {
-            Ty::Mono(ref v) => {
-                if let Some(t) = self.subst.get(v) {
-                    self.apply_subst(t.clone())
-                } else {
-                    ty
-                }
-            }
-            Ty::Arrow(a, b) => {
-                Ty::Arrow(
-                    Box::new(self.apply_subst(*a)),
-                    Box::new(self.apply_subst(*b)),
-                )
-            }
-            _ => ty,
+    /// Record the derivation of every judgement, see `derivations`
+    pub fn with_derivation(self) -> Self {
+        TypeChecker {
+            derivation: Some(Recorder::new()),
+            ..self
         }
     }
 
+    /// The derivation trees recorded so far, with the current solution of every
+    /// unification variable
+    pub fn derivations(&self) -> Option<Derivations> {
+        let recorder = self.derivation.as_ref()?;
+
+        let substitution = self.subst.bindings().collect();
+
+        Some(Derivations { roots: recorder.roots().to_vec(), substitution })
+    }
+
+    /// Open a step of the derivation, whose premises are recorded until `finish_step`
+    fn enter_step(&mut self, rule: &'static str, step: impl FnOnce() -> Step) {
+        if let Some(recorder) = &mut self.derivation {
+            recorder.enter(rule, step());
+        }
+    }
+
+    fn finish_step(&mut self, inferred: Option<Ty>, failed: bool) {
+        if let Some(recorder) = &mut self.derivation {
+            recorder.finish(inferred, failed);
+        }
+    }
+
+    fn axiom(&mut self, rule: &'static str, step: impl FnOnce() -> Step) {
+        if let Some(recorder) = &mut self.derivation {
+            recorder.axiom(rule, step());
+        }
+    }
+
+    /// Method implementations of all instances, for resolving method calls after monomorphization
+    pub fn method_table(&self) -> &MethodTable {
+        self.classes.methods()
+    }
+
+    /// A rigid type variable standing for the bound variable `var` of a polymorphic type,
+    /// numbered like the unification variables
+    fn fresh_skolem(&mut self, var: &TypeVar, binder: &str) -> TypeVar {
+        let skolem = format!("{}#{}", var, self.subst.fresh());
+        self.rigid.insert(skolem.clone(), binder.to_string());
+        skolem
+    }
+
+    /// Replace the bound variables of a polymorphic type with fresh skolems
+    fn skolemize(&mut self, vars: &[TypeVar], body: &Ty, binder: &str) -> (Vec<TypeVar>, Ty) {
+        let skolems: Vec<TypeVar> = vars.iter().map(|var| self.fresh_skolem(var, binder)).collect();
+        let subst = vars.iter().cloned()
+            .zip(skolems.iter().map(|skolem| Ty::Mono(skolem.clone())))
+            .collect();
+
+        (skolems, apply_subst(body.clone(), &subst))
+    }
+
     fn unify(&mut self, a: Ty, b: Ty) -> Result<(), FrontendError> {
-        let a = self.apply_subst(a);
-        let b = self.apply_subst(b);
+        let a = self.resolve(a);
+        let b = self.resolve(b);
+
+        self.enter_step("Unify", || Step::Unify { expected: a.clone(), found: b.clone() });
+        let unified = self.unify_rule(a, b);
+        self.finish_step(None, unified.is_err());
 
+        unified
+    }
+
+    /// Unify two types with the substitution already applied
+    fn unify_rule(&mut self, a: Ty, b: Ty) -> Result<(), FrontendError> {
         match (a, b) {
-            (Ty::Int, Ty::Int) | (Ty::Bool, Ty::Bool) => Ok(()),
-            (Ty::Mono(ref x), t) | (t, Ty::Mono(ref x)) => self.bind(x, t),
-            (Ty::Arrow(a1, a2), Ty::Arrow(b1, b2)) => {
+            (Ty::Int, Ty::Int) | (Ty::Bool, Ty::Bool) | (Ty::Unit, Ty::Unit) | (Ty::String, Ty::String) => Ok(()),
+            (Ty::Mono(x), Ty::Mono(y)) if x == y => Ok(()),
+            (Ty::Var(x), Ty::Var(y)) if x == y => Ok(()),
+            (var, t) | (t, var) if self.is_flexible(&var) => self.bind(var, t),
+            (Ty::Mono(x), t) | (t, Ty::Mono(x)) => Err(FrontendError::TypeError(format!(
+                "Cannot unify rigid type variable {} with {}",
+                self.describe_rigid(&x), t
+            ))),
+            (Ty::Arrow(a1, a2, _), Ty::Arrow(b1, b2, _)) => {
                 self.unify(*a1, *b1)?;
                 self.unify(*a2, *b2)
             }
+            (Ty::App(n1, args1), Ty::App(n2, args2)) if n1 == n2 && args1.len() == args2.len() => {
+                for (a, b) in args1.into_iter().zip(args2) {
+                    self.unify(a, b)?;
+                }
+
+                Ok(())
+            }
+            (Ty::Forall(v1, b1), Ty::Forall(v2, b2)) if v1.len() == v2.len() => {
+                // Polymorphic types are equal up to renaming of their bound variables
+                let (skolems, b1) = self.skolemize(&v1, &b1, "a polymorphic type");
+                let b2 = apply_subst(*b2, &v2.into_iter()
+                    .zip(skolems.into_iter().map(Ty::Mono))
+                    .collect());
+
+                self.unify(b1, b2)
+            }
             (a, b) => Err(FrontendError::TypeError(format!(
                 "Cannot unify types: {} and {}",
                 a, b
@@ -69,57 +252,238 @@ impl TypeChecker {
         }
     }
 
-    fn bind(&mut self, var: &TypeVar, ty: Ty) -> Result<(), FrontendError> {
-        if let Ty::Mono(ref x) = ty {
-            if var == x {
-                return Ok(()); // trivial
+    /// Unify `found` with `expected`, explaining a failure by `origin`
+    fn unify_because(&mut self, expected: Ty, found: Ty, origin: Option<&Origin>) -> Result<(), FrontendError> {
+        self.unify(expected.clone(), found.clone()).map_err(|error| {
+            let reason = match error {
+                FrontendError::TypeError(reason) => reason,
+                error => format!("{:?}", error),
+            };
+
+            let expected = self.resolve(expected);
+            let found = self.resolve(found);
+            FrontendError::TypeError(mismatch(origin, &expected, &found, &self.rigid, &reason))
+        })
+    }
+
+    /// Check `expr` against `expected`, which is required by `origin`
+    fn check_because(&mut self, expr: &mut ResolvedASTExpr, expected: &Ty, origin: Origin) -> Result<(), FrontendError> {
+        self.origins.push(origin);
+        let checked = self.check(expr, expected);
+        self.origins.pop();
+
+        checked
+    }
+
+    /// Solve the flexible type variable `var` to `ty`
+    fn bind(&mut self, var: Ty, ty: Ty) -> Result<(), FrontendError> {
+        self.solve_var(&var, ty.clone())?;
+        self.axiom("Bind", || Step::Bind { var: var.to_string(), ty });
+        Ok(())
+    }
+
+    /// Whether a value of type `scheme` could be used at type `ty`
+    fn fits(&mut self, scheme: &Scheme, ty: &Ty) -> bool {
+        let saved = self.subst.clone();
+        // Trial unifications are not part of the derivation
+        let recorder = self.derivation.take();
+
+        let (candidate, _, _) = self.instantiate(scheme);
+        let fits = self.unify(candidate, ty.clone()).is_ok();
+
+        self.subst = saved;
+        self.derivation = recorder;
+        fits
+    }
+
+    /// Describe each hole with its type and the bindings in scope that could fill it
+    fn report_holes(&mut self) -> String {
+        let mut report = Vec::new();
+
+        for hole in std::mem::take(&mut self.holes) {
+            let ty = self.resolve(hole.ty);
+            let name = match hole.name {
+                Some(name) => format!("?{}", name),
+                None => "_".to_string(),
+            };
+
+            let mut fits = Vec::new();
+            for (binding, scheme) in hole.bindings {
+                if self.fits(&scheme, &ty) {
+                    fits.push((binding, Scheme { ty: self.resolve(scheme.ty.clone()), ..scheme }));
+                }
+            }
+            fits.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            let mut printer = TyPrinter::new(&self.rigid);
+            report.push(format!("Found hole {} at {} : {}", name, hole.span, printer.ty(&ty)));
+
+            let fitting: Vec<String> = fits.iter()
+                .map(|(binding, scheme)| format!("    {} : {}", binding, printer.scheme(scheme)))
+                .collect();
+
+            if fitting.is_empty() {
+                report.push("  No bindings in scope fit".to_string());
+            } else {
+                report.push("  Relevant bindings include:".to_string());
+                report.extend(fitting);
             }
         }
 
-        if self.occurs_check(var, &ty) {
-            return Err(FrontendError::TypeError(format!(
-                "Cannot unify {} with {}, occurs check failed",
-                var, ty
-            )));
+        report.join("\n")
+    }
+
+    /// When slicing is requested, add every location that contributes to a type error
+    /// to its message, or say that the error cannot be sliced
+    fn explain(&self, error: FrontendError, slice: impl FnOnce() -> Option<Vec<Origin>>) -> FrontendError {
+        match error {
+            FrontendError::TypeError(message) if self.slice_errors => FrontendError::TypeError(match slice() {
+                Some(sites) => {
+                    let sites: Vec<String> = sites.iter().map(|site| format!("  - {}", site)).collect();
+                    format!("{}\nThe error involves all of these locations:\n{}", message, sites.join("\n"))
+                }
+                // e.g. a missing instance, or an error of a rule the slicer does not model
+                None => format!("{}\nNo slice is available for this error", message),
+            }),
+            error => error,
         }
+    }
 
-        self.subst.insert(var.to_string(), ty);
-        Ok(())
+    /// Check an instance method against the signature of its class method
+    fn check_method(&mut self, method: MethodImpl) -> Result<(ResolvedIdent, ResolvedASTExpr), FrontendError> {
+        let MethodImpl { ident, mut value, expected, origin } = method;
+
+        // Slicing starts over from the method as written
+        let original = self.slice_errors.then(|| (value.clone(), self.context.get_mapping().clone()));
+
+        self.check_because(&mut value, &expected, origin.clone()).map_err(|error| {
+            self.explain(error, || {
+                let (original, globals) = original?;
+                Slicer::slice_method(&original, &expected, origin, &globals)
+            })
+        })?;
+
+        Ok((ident, value))
     }
 
-    fn occurs_check(&self, var: &str, ty: &Ty) -> bool {
-        match ty {
-            Ty::Mono(x) => {
-                if x == var {
-                    true
-                } else if let Some(t) = self.subst.get(x) {
-                    self.occurs_check(var, t)
+    /// Type the value of a let binding and bring the binding into scope, returning
+    /// the type variables the value abstracts over
+    fn bind_let(
+        &mut self,
+        ident: &ResolvedIdent,
+        scheme: &mut Option<Scheme>,
+        value: &mut ResolvedASTExpr,
+        span: Span,
+    ) -> Result<Vec<TypeVar>, FrontendError> {
+        match scheme {
+            None => {
+                // Infer the type ourselves, one let level deeper
+                let wanted_start = self.wanted.len();
+                self.subst.enter_level();
+                let value_ty = self.infer(value);
+                self.subst.leave_level();
+                let value_ty = value_ty?;
+
+                // Only syntactic functions are generalized, so that every
+                // instance can be specialized by monomorphization
+                let inferred = if matches!(*value, ResolvedASTExpr::Lambda { .. }) {
+                    self.generalize(value_ty, wanted_start)
                 } else {
-                    false
+                    Scheme {
+                        ty: value_ty,
+                        constraints: vec![],
+                        vars: vec![],
+                    }
+                };
+
+                if matches!(*value, ResolvedASTExpr::Lambda { .. }) {
+                    self.axiom("Gen", || Step::Generalize { name: ident.name.clone(), scheme: inferred.clone() });
                 }
+
+                // Generalized bindings are marked polymorphic, just like annotated ones
+                if !inferred.vars.is_empty() {
+                    *scheme = Some(inferred.clone());
+                }
+
+                // Inferred. Insert into context
+                let ty_params = inferred.vars.clone();
+                self.bind_var(ident, inferred);
+
+                Ok(ty_params)
             }
-            Ty::Arrow(a, b) => self.occurs_check(var, a) || self.occurs_check(var, b),
-            _ => false,
-        }
-    }
+            Some(scheme) => {
+                self.kinds.check(&scheme.ty)?;
+
+                // Type variables of enclosing annotations keep their meaning here
+                scheme.ty = self.scoped_ty(scheme.ty.clone(), &scheme.vars);
+
+                // The quantified variables are rigid while checking the definition
+                let binder = format!("the annotation of {}", ident.name);
+                let (skolems, skolem_ty) = self.skolemize(&scheme.vars, &scheme.ty, &binder);
+                let skolem_of: HashMap<TypeVar, TypeVar> = self.scoped.clone().into_iter()
+                    .chain(scheme.vars.iter().cloned().zip(skolems.iter().cloned()))
+                    .collect();
+
+                // The constraints of the annotation may be assumed in the definition, and only there
+                let outer_given = self.given.clone();
+                let wanted_start = self.wanted.len();
+                self.given.extend(scheme.constraints.iter().map(|constraint| TypeClassConstraint {
+                    class: constraint.class.clone(),
+                    type_var: skolem_of.get(&constraint.type_var)
+                        .cloned()
+                        .unwrap_or_else(|| constraint.type_var.clone()),
+                }));
+
+                // Insert into context before passing
+                // to support for self-recursive let bindings
+                self.bind_var(
+                    ident,
+                    scheme.clone()
+                );
 
+                // Nested annotations may refer to the quantified variables
+                let outer_scope = self.scoped.clone();
+                self.scoped.extend(scheme.vars.iter().cloned().zip(skolems.iter().cloned()));
 
-    /// Instantiate a type scheme by replacing its type variables with fresh type variables.
-    pub fn instantiate(&mut self, scheme: &Scheme) -> Ty {
-        let mut subst = HashMap::new();
-        for var in &scheme.vars {
-            let fresh_var = self.fresh_ty();
-            subst.insert(var.clone(), fresh_var);
-        }
+                let origin = Origin::Annotation { name: ident.name.clone(), span };
+                let checked = self.check_because(value, &skolem_ty, origin);
+                self.scoped = outer_scope;
+
+                if checked.is_ok() {
+                    self.discharge_given(wanted_start);
+                }
+                self.given = outer_given;
+                checked?;
+
+                self.check_escape(&skolems, &scheme.ty)?;
 
-        apply_subst(scheme.ty.clone(), subst)
+                Ok(skolems)
+            }
+        }
     }
 
     pub fn infer(&mut self, expr: &mut ResolvedASTExpr) -> Result<Ty, FrontendError> {
-        println!("Inferring type for expression: {:?}", expr);
-        
+        let rule = match expr {
+            ResolvedASTExpr::Atom(ASTAtom::Var(_), _, _) => "Var",
+            ResolvedASTExpr::Atom(ASTAtom::Op(_), _, _) => "Op",
+            ResolvedASTExpr::Atom(ASTAtom::Hole(..), _, _) => "Hole",
+            ResolvedASTExpr::Atom(..) => "Lit",
+            ResolvedASTExpr::If { .. } => "If",
+            ResolvedASTExpr::Let { .. } => "Let",
+            ResolvedASTExpr::Apply { .. } => "App",
+            ResolvedASTExpr::Lambda { .. } => "Abs",
+        };
+
+        self.enter_step(rule, || Step::Infer { expr: source(expr), ty: None });
+        let inferred = self.infer_rule(expr);
+        self.finish_step(inferred.as_ref().ok().cloned(), inferred.is_err());
+
+        inferred
+    }
+
+    fn infer_rule(&mut self, expr: &mut ResolvedASTExpr) -> Result<Ty, FrontendError> {
         let primitive = match expr {
-            ResolvedASTExpr::Atom(atom, atom_ty) => {
+            ResolvedASTExpr::Atom(atom, atom_ty, atom_ty_args) => {
                 match atom {
                     ASTAtom::Int(_) => {
                         *atom_ty = Some(Ty::Int);
@@ -129,11 +493,27 @@ impl TypeChecker {
                     ASTAtom::Var(ident) => {
                         // lookup in context
                         let scheme = self.context.get(&ident.id).ok_or_else(|| {
-                            FrontendError::UnboundVariable(ident.name.clone())
+                            FrontendError::UnboundVariable(UnboundName::new(ident.name.clone()))
                         })?.clone();
 
                         // instantiate the type scheme
-                        let ty = self.instantiate(&scheme);
+                        let (mut ty, constraints, ty_args) = self.instantiate(&scheme);
+                        self.wanted.extend(constraints);
+                        *atom_ty_args = ty_args;
+
+                        if !scheme.vars.is_empty() {
+                            self.axiom("Inst", || Step::Instantiate {
+                                name: ident.name.clone(),
+                                scheme: scheme.clone(),
+                                ty: ty.clone(),
+                            });
+                        }
+
+                        // A variable of polymorphic type, e.g. a higher-rank argument, is instantiated at each use
+                        while let Ty::Forall(vars, body) = self.resolve(ty.clone()) {
+                            let subst = vars.into_iter().map(|var| (var, self.fresh_ty())).collect();
+                            ty = apply_subst(*body, &subst);
+                        }
 
                         // Set the type of the atom
                         *atom_ty = Some(ty.clone());
@@ -146,15 +526,16 @@ impl TypeChecker {
                                 // Arithmetic operations expect two integers for now
                                 Ty::Arrow(
                                     Box::new(Ty::Int),
-                                    Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int))),
+                                    Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int), Effect::PURE)),
+                                    Effect::PURE,
                                 )
                             },
                             OpType::Eq | OpType::Neq | OpType::Gt | OpType::Lt | OpType::Geq | OpType::Leq => {
-                                // Equality operations now only work with integers
-                                Ty::Arrow(
-                                    Box::new(Ty::Int),
-                                    Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Bool))),
-                                )
+                                // Comparisons are overloaded on any instance of Eq / Ord
+                                let (op_ty, constraints, _) = self.instantiate(&comparison_scheme(op));
+                                self.wanted.extend(constraints);
+
+                                op_ty
                             }
                         };
                         
@@ -172,76 +553,92 @@ impl TypeChecker {
                         
                         Ok(Ty::Bool)
                     },
+                    ASTAtom::Str(_) => {
+                        *atom_ty = Some(Ty::String);
+
+                        Ok(Ty::String)
+                    },
+                    ASTAtom::Hole(name, span) => {
+                        // Anything may fill the hole, its type is found by unification
+                        let ty = self.fresh_ty();
+
+                        // Of the bindings sharing a name, only the innermost one is visible
+                        let mut visible: HashMap<&String, (usize, &Scheme)> = HashMap::new();
+                        for (id, scheme) in self.context.get_mapping() {
+                            if let Some((name, order)) = self.names.get(id)
+                                && visible.get(name).is_none_or(|(seen, _)| seen < order)
+                            {
+                                visible.insert(name, (*order, scheme));
+                            }
+                        }
+
+                        let bindings = visible.into_iter()
+                            .map(|(name, (_, scheme))| (name.clone(), scheme.clone()))
+                            .collect();
+
+                        self.holes.push(Hole { name: name.clone(), span: *span, ty: ty.clone(), bindings });
+                        *atom_ty = Some(ty.clone());
+
+                        Ok(ty)
+                    },
                 }
             }
-            ResolvedASTExpr::If { cond, then, else_, ty } => {
+            ResolvedASTExpr::If { cond, then, else_, ty, span } => {
                 let cond_ty = self.infer(cond)?;
 
-                self.unify(cond_ty, Ty::Bool)?;
+                self.unify_because(Ty::Bool, cond_ty, Some(&Origin::IfCondition(*span)))?;
 
                 let then_ty = self.infer(then)?;
                 let else_ty = self.infer(else_)?;
 
-                self.unify(then_ty.clone(), else_ty)?;
+                self.unify_because(then_ty.clone(), else_ty, Some(&Origin::IfBranches(*span)))?;
 
-                let then_ty = self.apply_subst(then_ty);
+                let then_ty = self.resolve(then_ty);
 
                 *ty = Some(then_ty.clone());
                 Ok(then_ty)
             }
-            ResolvedASTExpr::Let { bind: (ident, scheme), value, body, ty } => {
-                match scheme {
-                    None => {
-                        // Infer the type ourselves
-                        println!("No type scheme provided for let binding: {}", ident.id.0);
-                        
-                        let value_ty = self.infer(value)?;
-
-                        // Inferred. Insert into context
-                        self.context.insert(
-                            ident.id.clone(),
-                            Scheme {
-                                ty: value_ty.clone(),
-                                constraints: vec![],
-                                vars: vec![],
-                            },
-                        )
-                    }
-                    Some(scheme) => {
-                        println!("Using type scheme for let binding: {}", ident.id.0);
-                        let instantiated_ty = self.instantiate(scheme);
-
-                        // Insert into context before passing
-                        // to support for self-recursive let bindings
-                        self.context.insert(
-                            ident.id.clone(),
-                            scheme.clone()
-                        );
-
-                        // Check the value against the instantiated type
-                        self.check(value, &instantiated_ty)?;
-                    }
-                }
+            ResolvedASTExpr::Let { bind: (ident, scheme), value, body, ty, ty_params, span } => {
+                *ty_params = self.bind_let(ident, scheme, value, *span)?;
 
                 // Infer the type of the body
                 let body_ty = self.infer(body)?;
 
-                let body_ty = self.apply_subst(body_ty);
+                let body_ty = self.resolve(body_ty);
+
+                // The binding goes out of scope
+                self.context.remove(&ident.id);
 
                 // Set the type of the let expression
                 *ty = Some(body_ty.clone());
                 Ok(body_ty)
             }
-            ResolvedASTExpr::Apply { func, args, ty } => {
+            ResolvedASTExpr::Apply { func, args, ty, span } => {
                 let func_ty = self.infer(func)?;
-                let arg_ty = self.infer(args)?;
 
-                let ret_ty = self.fresh_ty();
+                let ret_ty = match self.resolve(func_ty) {
+                    Ty::Arrow(param_ty, ret_ty, _) => {
+                        // The parameter type is known, check the argument against it
+                        let (callee, applied) = call_head(func);
+                        let origin = Origin::Argument { index: applied + 1, callee, span: *span };
+                        self.check_because(args, &param_ty, origin)?;
+
+                        *ret_ty
+                    }
+                    func_ty => {
+                        let arg_ty = self.infer(args)?;
+
+                        let ret_ty = self.fresh_ty();
+
+                        // Unify the function type with the expected type
+                        let expected = Ty::Arrow(Box::new(arg_ty), Box::new(ret_ty.clone()), Effect::PURE);
+                        self.unify_because(expected, func_ty, Some(&Origin::Callee(*span)))?;
 
-                // Unify the function type with the expected type
-                self.unify(func_ty, Ty::Arrow(Box::new(arg_ty), Box::new(ret_ty.clone())))?;
+                        ret_ty
+                    }
+                };
 
-                let ret_ty = self.apply_subst(ret_ty);
+                let ret_ty = self.resolve(ret_ty);
 
                 // Set the type of the application
                 *ty = Some(ret_ty.clone());
@@ -249,15 +646,24 @@ impl TypeChecker {
                 // Return the return type of the function
                 Ok(ret_ty)
             }
-            ResolvedASTExpr::Lambda { arg: (ident, ty_opt), body, ret_ty } => {
-                // In infer mode, we expect the argument type to be provided
-                let arg_ty = ty_opt.clone().ok_or_else(|| {
-                    FrontendError::TypeError("Lambda argument type is not provided".to_string())
-                })?;
+            ResolvedASTExpr::Lambda { arg: (ident, ty_opt), body, ret_ty, .. } => {
+                // Without an annotation, the argument type is left to unification
+                let arg_ty = match ty_opt {
+                    Some(ty) => {
+                        self.kinds.check(ty)?;
+                        *ty = self.scoped_ty(ty.clone(), &[]);
+                        ty.clone()
+                    }
+                    None => {
+                        let fresh = self.fresh_ty();
+                        *ty_opt = Some(fresh.clone());
+                        fresh
+                    }
+                };
 
                 // Bind the argument type in the context
-                self.context.insert(
-                    ident.id.clone(),
+                self.bind_var(
+                    ident,
                     Scheme {
                         ty: arg_ty.clone(),
                         constraints: vec![],
@@ -268,7 +674,9 @@ impl TypeChecker {
                 // Infer the body type
                 let body_ty = self.infer(body)?;
 
-                let body_ty = self.apply_subst(body_ty);
+                let body_ty = self.resolve(body_ty);
+
+                self.context.remove(&ident.id);
 
                 // set the return type of the lambda
                 *ret_ty = Some(body_ty.clone());
@@ -276,58 +684,118 @@ impl TypeChecker {
                 Ok(Ty::Arrow(
                     Box::new(arg_ty),
                     Box::new(body_ty.clone()),
+                    Effect::PURE,
                 ))
             }
         }?;
 
-        let substituted = self.apply_subst(primitive);
+        let substituted = self.resolve(primitive);
 
         Ok(substituted)
     }
 
     pub fn check(&mut self, expr: &mut ResolvedASTExpr, expected: &Ty) -> Result<(), FrontendError> {
-        match expr {
-            ResolvedASTExpr::Lambda { arg: (ident, ty_opt), body, ret_ty } => {
-                if let Ty::Arrow(arg_expected, ret_expected) = expected {
-                    // Insert the argument type into the context
-                    self.context.insert(
-                        ident.id.clone(),
-                        Scheme {
-                            ty: *arg_expected.clone(),
-                            constraints: vec![],
-                            vars: vec![],
-                        },
-                    );
-
-                    // Check the body against the return type
-                    self.check(body, ret_expected)?;
-
-                    // Success, set the type of the lambda
-                    let ret_expected = self.apply_subst(*ret_expected.clone());
-                    let arg_expected = self.apply_subst(*arg_expected.clone());
-
-                    *ret_ty = Some(ret_expected);
-                    *ty_opt = Some(arg_expected);
-
-                    Ok(())
-                } else {
-                    Err(FrontendError::TypeError(format!(
-                        "Expected a function type, found: {}",
-                        expected
-                    )))
+        let expected = &self.resolve(expected.clone());
+
+        let rule = match (&*expr, expected) {
+            (_, Ty::Forall(..)) => "Skol",
+            (ResolvedASTExpr::Lambda { .. }, ty) if !matches!(ty, Ty::Mono(_) | Ty::Var(_)) => "Abs-Chk",
+            (ResolvedASTExpr::If { .. }, _) => "If-Chk",
+            (ResolvedASTExpr::Let { .. }, _) => "Let-Chk",
+            _ => "Sub",
+        };
+
+        self.enter_step(rule, || Step::Check { expr: source(expr), ty: expected.clone() });
+        let checked = self.check_rule(expr, expected);
+        self.finish_step(None, checked.is_err());
+
+        checked
+    }
+
+    fn check_rule(&mut self, expr: &mut ResolvedASTExpr, expected: &Ty) -> Result<(), FrontendError> {
+
+        match (&mut *expr, expected) {
+            (_, Ty::Forall(vars, body)) => {
+                // The expression must be polymorphic: check it against fresh skolems
+                let binder = format!("the polymorphic type {}", expected);
+                let (skolems, body) = self.skolemize(vars, body, &binder);
+                self.check(expr, &body)?;
+
+                // A skolem must not leak into the types of the enclosing bindings
+                self.check_escape(&skolems, expected)
+            }
+            (ResolvedASTExpr::Lambda { arg: (ident, ty_opt), body, ret_ty, span }, Ty::Arrow(arg_expected, ret_expected, _)) => {
+                // An annotated argument must agree with the expected parameter type
+                if let Some(annotated) = ty_opt {
+                    self.kinds.check(annotated)?;
+                    let annotated = self.scoped_ty(annotated.clone(), &[]);
+                    let origin = Origin::ParamAnnotation { name: ident.name.clone(), span: *span };
+                    self.unify_because(*arg_expected.clone(), annotated, Some(&origin))?;
                 }
+
+                // Insert the argument type into the context
+                self.bind_var(
+                    ident,
+                    Scheme {
+                        ty: *arg_expected.clone(),
+                        constraints: vec![],
+                        vars: vec![],
+                    },
+                );
+
+                // Check the body against the return type
+                self.check(body, ret_expected)?;
+
+                self.context.remove(&ident.id);
+
+                // Success, set the type of the lambda
+                let ret_expected = self.resolve(*ret_expected.clone());
+                let arg_expected = self.resolve(*arg_expected.clone());
+
+                *ret_ty = Some(ret_expected);
+                *ty_opt = Some(arg_expected);
+
+                Ok(())
             },
+            (ResolvedASTExpr::Lambda { .. }, expected) if !matches!(expected, Ty::Mono(_) | Ty::Var(_)) => {
+                Err(FrontendError::TypeError(format!(
+                    "Expected a function type, found: {}",
+                    expected
+                )))
+            }
+            (ResolvedASTExpr::If { cond, then, else_, ty, span }, _) => {
+                self.check_because(cond, &Ty::Bool, Origin::IfCondition(*span))?;
+
+                // Both branches are checked against the expected type
+                self.check(then, expected)?;
+                self.check(else_, expected)?;
+
+                *ty = Some(self.resolve(expected.clone()));
+                Ok(())
+            }
+            (ResolvedASTExpr::Let { bind: (ident, scheme), value, body, ty, ty_params, span }, _) => {
+                *ty_params = self.bind_let(ident, scheme, value, *span)?;
+
+                // The body is checked against the expected type
+                self.check(body, expected)?;
+
+                self.context.remove(&ident.id);
+
+                *ty = Some(self.resolve(expected.clone()));
+                Ok(())
+            }
             _ => {
                 // For other expressions, we just infer and unify
                 let inferred = self.infer(expr)?;
-                self.unify(inferred, expected.clone())?;
+                let origin = self.origins.last().cloned();
+                self.unify_because(expected.clone(), inferred, origin.as_ref())?;
 
                 // After successful unification, apply substitution to expected type
-                let final_ty = self.apply_subst(expected.clone());
+                let final_ty = self.resolve(expected.clone());
 
                 // Store the type in the AST node
                 match expr {
-                    ResolvedASTExpr::Atom(_atom, ty) => {
+                    ResolvedASTExpr::Atom(_atom, ty, _) => {
                         *ty = Some(final_ty);
                     }
                     ResolvedASTExpr::If { ty, .. } => {
@@ -339,9 +807,8 @@ impl TypeChecker {
                     ResolvedASTExpr::Apply { ty, .. } => {
                         *ty = Some(final_ty);
                     }
-                    ResolvedASTExpr::Lambda { ret_ty, .. } => {
-                        *ret_ty = Some(final_ty);
-                    }
+                    // Inference has set the parameter and result types, `final_ty` is the whole function type
+                    ResolvedASTExpr::Lambda { .. } => {}
                 }
 
                 Ok(())
@@ -349,69 +816,86 @@ impl TypeChecker {
         }
     }
 
+    /// Replace the type variables of every annotation by their solutions, naming
+    /// the unsolved ones
     pub fn final_apply(&self, ast: &mut ResolvedASTExpr) {
         match ast {
-            ResolvedASTExpr::Atom(_, ty) => {
+            ResolvedASTExpr::Atom(_, ty, ty_args) => {
                 if let Some(t) = ty {
-                    *ty = Some(self.apply_subst(t.clone()));
+                    *ty = Some(self.subst.apply_named(t.clone()));
+                }
+                for ty_arg in ty_args {
+                    *ty_arg = self.subst.apply_named(ty_arg.clone());
                 }
             }
-            ResolvedASTExpr::If { cond, then, else_, ty } => {
-                *ty = Some(self.apply_subst(ty.clone().unwrap()));
+            ResolvedASTExpr::If { cond, then, else_, ty, .. } => {
+                *ty = Some(self.subst.apply_named(ty.clone().unwrap()));
                 self.final_apply(cond);
                 self.final_apply(then);
                 self.final_apply(else_);
             }
             ResolvedASTExpr::Let { value, body, ty, .. } => {
-                *ty = Some(self.apply_subst(ty.clone().unwrap()));
+                *ty = Some(self.subst.apply_named(ty.clone().unwrap()));
                 self.final_apply(value);
                 self.final_apply(body);
             }
-            ResolvedASTExpr::Apply { func, args, ty } => {
-                *ty = Some(self.apply_subst(ty.clone().unwrap()));
+            ResolvedASTExpr::Apply { func, args, ty, .. } => {
+                *ty = Some(self.subst.apply_named(ty.clone().unwrap()));
                 self.final_apply(func);
                 self.final_apply(args);
             }
-            ResolvedASTExpr::Lambda { body, ret_ty, .. } => {
-                *ret_ty = Some(self.apply_subst(ret_ty.clone().unwrap()));
+            ResolvedASTExpr::Lambda { arg: (_, arg_ty), body, ret_ty, .. } => {
+                *arg_ty = Some(self.subst.apply_named(arg_ty.clone().unwrap()));
+                *ret_ty = Some(self.subst.apply_named(ret_ty.clone().unwrap()));
                 self.final_apply(body);
             }
         }
     }
 
-    pub fn tyck(&mut self, ast: ResolvedASTExpr) -> Result<TypedASTExpr, FrontendError> {
-        fn unwrap_ast_expr(expr: ResolvedASTExpr) -> TypedASTExpr {
-            match expr {
-                ResolvedASTExpr::Atom(atom, ty) => ASTExpr::Atom(atom, ty.unwrap()),
-                ResolvedASTExpr::If { cond, then, else_, ty } => ASTExpr::If {
-                    cond: Box::new(unwrap_ast_expr(*cond)),
-                    then: Box::new(unwrap_ast_expr(*then)),
-                    else_: Box::new(unwrap_ast_expr(*else_)),
-                    ty: ty.unwrap(),
-                },
-                ResolvedASTExpr::Let { bind, value, body, ty } => ASTExpr::Let {
-                    bind,
-                    value: Box::new(unwrap_ast_expr(*value)),
-                    body: Box::new(unwrap_ast_expr(*body)),
-                    ty: ty.unwrap(),
-                },
-                ResolvedASTExpr::Apply { func, args, ty } => ASTExpr::Apply {
-                    func: Box::new(unwrap_ast_expr(*func)),
-                    args: Box::new(unwrap_ast_expr(*args)),
-                    ty: ty.unwrap(),
-                },
-                ResolvedASTExpr::Lambda { arg: (ident, arg_ty), body, ret_ty } => ASTExpr::Lambda {
-                    arg: (ident, arg_ty.unwrap()),
-                    body: Box::new(unwrap_ast_expr(*body)),
-                    ret_ty: ret_ty.unwrap(),
-                },
+    pub fn tyck(&mut self, program: ResolvedProgram) -> Result<TypedASTExpr, FrontendError> {
+        let mut impls = Vec::new();
+
+        for decl in program.decls {
+            match decl {
+                Decl::Type(decl) => self.kinds.declare(&decl)?,
+                Decl::Class(class) => self.declare_class(class)?,
+                Decl::Instance(instance) => {
+                    for method in self.declare_instance(instance)? {
+                        impls.push(self.check_method(method)?);
+                    }
+                }
             }
         }
 
-        let mut ast = ast;
+        let mut ast = program.body;
+
+        // Slicing starts over from the program as written
+        let original = self.slice_errors.then(|| (ast.clone(), self.context.get_mapping().clone()));
 
         // Infer the type of the expression
-        self.infer(&mut ast)?;
+        let ty = match self.infer(&mut ast) {
+            Ok(ty) => ty,
+            Err(error) => {
+                let error = self.explain(error, || {
+                    let (original, globals) = original?;
+                    Slicer::slice(&original, &globals)
+                });
+
+                // The holes met before the error are reported with it
+                if self.holes.is_empty() {
+                    return Err(error);
+                }
+                return Err(FrontendError::TypedHoles(format!("{}\nerror: {}", self.report_holes(), error)));
+            }
+        };
+
+        // A program with holes is only checked, never compiled
+        if !self.holes.is_empty() {
+            return Err(FrontendError::TypedHoles(self.report_holes()));
+        }
+
+        // All class constraints must be resolved by now
+        self.solve_classes().map_err(|error| self.explain(error, || None))?;
 
         // Apply final substitutions to the AST
         self.final_apply(&mut ast);
@@ -420,12 +904,77 @@ impl TypeChecker {
         // println!("Final AST: {}", pretty_expr(&ast, 0));
 
         // Unwrap the expression into a fully-typed AST
-        let typed_ast = unwrap_ast_expr(ast);
+        let mut typed_ast = unwrap_ast_expr(ast);
+
+        // Instance methods are bound around the program, so that method calls
+        // can be resolved to them during monomorphization
+        let ty = self.subst.apply_named(ty);
+        for (impl_ident, mut value) in impls.into_iter().rev() {
+            self.final_apply(&mut value);
+
+            typed_ast = ASTExpr::Let {
+                bind: (impl_ident, None),
+                value: Box::new(unwrap_ast_expr(value)),
+                body: Box::new(typed_ast),
+                ty: ty.clone(),
+                ty_params: vec![],
+                span: Span::default(),
+            };
+        }
         
         Ok(typed_ast)
     }
 }
 
+impl Inference for TypeChecker {
+    fn context(&self) -> &TypingContext {
+        &self.context
+    }
+
+    fn classes(&self) -> &ClassEnv {
+        &self.classes
+    }
+
+    fn classes_mut(&mut self) -> &mut ClassEnv {
+        &mut self.classes
+    }
+
+    fn subst(&self) -> &UnionFind {
+        &self.subst
+    }
+
+    fn subst_mut(&mut self) -> &mut UnionFind {
+        &mut self.subst
+    }
+
+    fn wanted(&mut self) -> &mut Vec<Wanted> {
+        &mut self.wanted
+    }
+
+    fn given(&self) -> &[TypeClassConstraint] {
+        &self.given
+    }
+
+    fn scoped(&self) -> &HashMap<TypeVar, TypeVar> {
+        &self.scoped
+    }
+
+    fn rigid(&self) -> &HashMap<TypeVar, String> {
+        &self.rigid
+    }
+
+    fn annotation(&self, ty: &Ty) -> Result<(), FrontendError> {
+        self.kinds.check(ty)
+    }
+
+    /// Bind `ident` in the context, remembering its name for diagnostics
+    fn bind_var(&mut self, ident: &ResolvedIdent, scheme: Scheme) {
+        let order = self.names.len();
+        self.names.entry(ident.id.clone()).or_insert_with(|| (ident.name.clone(), order));
+        self.context.insert(ident.id.clone(), scheme);
+    }
+}
+
 impl Display for TypeChecker {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Display typing context
@@ -446,13 +995,13 @@ impl Display for TypeChecker {
         if self.subst.is_empty() {
             writeln!(f, "  <empty>")?;
         } else {
-            for (var, ty) in &self.subst {
+            for (var, ty) in self.subst.bindings() {
                 writeln!(f, "  {} ↦ {}", var, ty)?;
             }
         }
 
         // Display fresh variable counter
-        writeln!(f, "\nFresh variable counter: {}", self.fresh)?;
+        writeln!(f, "\nFresh variable counter: {}", self.subst.len())?;
 
         Ok(())
     }
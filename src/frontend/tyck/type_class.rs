@@ -1,16 +1,211 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fmt::Display;
+use crate::frontend::FrontendError;
+use crate::frontend::name_resolution::{NameIdentifier, ResolvedIdent};
+use crate::frontend::ty::{Scheme, Ty, TypeVar};
 
 /// The structure representing a type class constraint in the type system.
 /// `Class type_var` indicates that the `type_var` must be an instance of the `class`.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TypeClassConstraint {
     pub class: String,
     pub type_var: String,
 }
 
+impl TypeClassConstraint {
+    /// The parser reads the constraint `Show a` as the type application `Show a`
+    pub fn from_ty(ty: Ty) -> Option<Self> {
+        match ty {
+            Ty::App(class, mut args) if args.len() == 1 => match args.pop() {
+                Some(Ty::Mono(type_var)) => Some(TypeClassConstraint { class, type_var }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// A class constraint raised while inferring, on a type that may still be a unification variable
+#[derive(Debug, Clone)]
+pub struct Wanted {
+    pub class: String,
+    pub ty: Ty,
+}
+
 impl Display for TypeClassConstraint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} {}", self.class, self.type_var)
     }
 }
+
+/// Built-in class of `==` and `!=`
+pub const EQ_CLASS: &str = "Eq";
+/// Built-in class of `<`, `>`, `<=` and `>=`
+pub const ORD_CLASS: &str = "Ord";
+
+pub fn is_comparison_class(class: &str) -> bool {
+    class == EQ_CLASS || class == ORD_CLASS
+}
+
+/// The scheme of a method of `class` with signature `ty`. The class parameter
+/// comes first, the other variables of the signature are quantified as well.
+pub fn method_scheme(class: &str, type_var: &TypeVar, ty: &Ty) -> Scheme {
+    let mut vars = vec![type_var.clone()];
+    let mut others: Vec<TypeVar> = ty.free_vars().into_iter().filter(|v| v != type_var).collect();
+    others.sort();
+    vars.extend(others);
+
+    Scheme {
+        vars,
+        constraints: vec![TypeClassConstraint {
+            class: class.to_string(),
+            type_var: type_var.clone(),
+        }],
+        ty: ty.clone(),
+    }
+}
+
+/// A declared class: its parameter and the signatures of its methods
+#[derive(Debug, Clone)]
+pub struct ClassInfo {
+    pub type_var: TypeVar,
+    pub methods: Vec<(ResolvedIdent, Ty)>,
+}
+
+/// All classes and instances known to the type checker
+#[derive(Debug, Default)]
+pub struct ClassEnv {
+    classes: HashMap<String, ClassInfo>,
+    instances: HashSet<(String, Ty)>,
+    methods: MethodTable,
+}
+
+impl ClassEnv {
+    pub fn new() -> Self {
+        let mut env = ClassEnv::default();
+
+        // Comparisons have no methods, the backend emits them per instance type
+        for class in [EQ_CLASS, ORD_CLASS] {
+            env.classes.insert(class.to_string(), ClassInfo {
+                type_var: "a".to_string(),
+                methods: vec![],
+            });
+
+            for ty in [Ty::Int, Ty::Bool, Ty::String] {
+                env.instances.insert((class.to_string(), ty));
+            }
+        }
+
+        env
+    }
+
+    pub fn add_class(&mut self, name: String, info: ClassInfo) -> Result<(), FrontendError> {
+        if self.classes.contains_key(&name) {
+            return Err(FrontendError::TypeError(format!("Class {} is declared twice", name)));
+        }
+
+        for (method, ty) in &info.methods {
+            self.methods.insert_method(method, info.type_var.clone(), ty.clone());
+        }
+
+        self.classes.insert(name, info);
+        Ok(())
+    }
+
+    pub fn get_class(&self, name: &str) -> Option<&ClassInfo> {
+        self.classes.get(name)
+    }
+
+    pub fn add_instance(&mut self, class: &str, ty: Ty) -> Result<(), FrontendError> {
+        if !self.instances.insert((class.to_string(), ty.clone())) {
+            return Err(FrontendError::TypeError(format!(
+                "Duplicate instance {} {}", class, ty
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn has_instance(&self, class: &str, ty: &Ty) -> bool {
+        self.instances.contains(&(class.to_string(), ty.clone()))
+    }
+
+    pub fn methods(&self) -> &MethodTable {
+        &self.methods
+    }
+
+    pub fn methods_mut(&mut self) -> &mut MethodTable {
+        &mut self.methods
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodInfo {
+    /// The class parameter, as it appears in `ty`
+    pub type_var: TypeVar,
+    pub ty: Ty,
+    /// Instance type => identifier of the implementation
+    pub impls: HashMap<Ty, ResolvedIdent>,
+}
+
+/// Method implementations of every instance, so that method calls can be
+/// resolved at compile time once the instance type is known
+#[derive(Debug, Default, Clone)]
+pub struct MethodTable {
+    methods: HashMap<NameIdentifier, MethodInfo>,
+}
+
+impl MethodTable {
+    pub fn insert_method(&mut self, method: &ResolvedIdent, type_var: TypeVar, ty: Ty) {
+        self.methods.insert(method.id.clone(), MethodInfo {
+            type_var,
+            ty,
+            impls: HashMap::new(),
+        });
+    }
+
+    pub fn insert_impl(&mut self, method: &NameIdentifier, instance: Ty, impl_ident: ResolvedIdent) {
+        if let Some(info) = self.methods.get_mut(method) {
+            info.impls.insert(instance, impl_ident);
+        }
+    }
+
+    pub fn is_method(&self, id: &NameIdentifier) -> bool {
+        self.methods.contains_key(id)
+    }
+
+    /// The implementations of `method` in every instance
+    pub fn impls(&self, method: &NameIdentifier) -> Vec<&ResolvedIdent> {
+        self.methods.get(method)
+            .map(|info| info.impls.values().collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether `id` is the implementation of a method in some instance
+    pub fn is_impl(&self, id: &NameIdentifier) -> bool {
+        self.methods.values().any(|info| info.impls.values().any(|impl_ident| impl_ident.id == *id))
+    }
+
+    /// Find the implementation of `method` when used at the (monomorphic) type `ty`
+    pub fn resolve(&self, method: &NameIdentifier, ty: &Ty) -> Option<ResolvedIdent> {
+        let info = self.methods.get(method)?;
+        let instance = match_class_var(&info.ty, ty, &info.type_var)?;
+
+        info.impls.get(&instance).cloned()
+    }
+}
+
+/// Match the method signature `pattern` against `ty`, returning what the class parameter `var` stands for
+fn match_class_var(pattern: &Ty, ty: &Ty, var: &TypeVar) -> Option<Ty> {
+    match (pattern, ty) {
+        (Ty::Mono(v), _) if v == var => Some(ty.clone()),
+        (Ty::Arrow(p1, p2, _), Ty::Arrow(t1, t2, _)) => {
+            match_class_var(p1, t1, var).or_else(|| match_class_var(p2, t2, var))
+        }
+        (Ty::App(n1, ps), Ty::App(n2, ts)) if n1 == n2 => {
+            ps.iter().zip(ts).find_map(|(p, t)| match_class_var(p, t, var))
+        }
+        _ => None,
+    }
+}
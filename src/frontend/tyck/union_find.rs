@@ -0,0 +1,288 @@
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use crate::frontend::ty::{Ty, TypeVar, VarId};
+
+/// A node of the forest: a unification variable, or a declared annotation variable
+type Slot = usize;
+
+/// The solutions of the type variables, as a union-find forest.
+/// Unification variables are `Ty::Var`s of integer ids. Variables written in annotations
+/// stay `Ty::Mono` in types, and get a node of the forest once declared, so that they
+/// are solved the same way without taking up the ids of unification variables.
+///
+/// Variables unified with each other share a root, which holds the type they are
+/// solved to, if any. Each class is printed as the variable it was last unified
+/// into, so the resolved types do not depend on the shape of the forest.
+///
+/// Every class also has the let level it is bound at: the lowest level of a variable
+/// it was unified with or occurs in the solution of. A let generalizes the variables
+/// deeper than itself, the others are reachable from the context.
+#[derive(Debug, Clone, Default)]
+pub struct UnionFind {
+    // The node of each unification variable
+    slots: Vec<Slot>,
+    // The annotation variables declared so far
+    named: HashMap<TypeVar, Slot>,
+    // The variable of each node, as it appears in types
+    vars: Vec<Ty>,
+    // Compressed on every `find`, which only needs shared access
+    parent: Vec<Cell<Slot>>,
+    rank: Vec<u32>,
+    // On roots: the node standing for the class, its solution and its level
+    repr: Vec<Slot>,
+    solution: Vec<Option<Ty>>,
+    level: Vec<usize>,
+    // The number of lets whose value is being typed
+    current_level: usize,
+}
+
+impl UnionFind {
+    pub fn new() -> Self {
+        UnionFind::default()
+    }
+
+    fn push(&mut self, var: Ty) -> Slot {
+        let slot = self.vars.len();
+        self.vars.push(var);
+        self.parent.push(Cell::new(slot));
+        self.rank.push(0);
+        self.repr.push(slot);
+        self.solution.push(None);
+        self.level.push(self.current_level);
+        slot
+    }
+
+    /// A new unsolved variable at the current level
+    pub fn fresh(&mut self) -> VarId {
+        let id = self.slots.len();
+        let slot = self.push(Ty::Var(id));
+        self.slots.push(slot);
+        id
+    }
+
+    /// Make the annotation variable `var` flexible, at the current level on first use
+    pub fn declare(&mut self, var: &TypeVar) {
+        if !self.named.contains_key(var) {
+            let slot = self.push(Ty::Mono(var.clone()));
+            self.named.insert(var.clone(), slot);
+        }
+    }
+
+    /// Start typing the value of a let, whose variables may be generalized
+    pub fn enter_level(&mut self) {
+        self.current_level += 1;
+    }
+
+    pub fn leave_level(&mut self) {
+        self.current_level -= 1;
+    }
+
+    fn find(&self, slot: Slot) -> Slot {
+        let mut root = slot;
+        while self.parent[root].get() != root {
+            root = self.parent[root].get();
+        }
+
+        // Path compression
+        let mut current = slot;
+        while current != root {
+            current = self.parent[current].replace(root);
+        }
+
+        root
+    }
+
+    /// The root of the class of `ty`, if it is a unification variable or a declared
+    /// annotation variable
+    pub fn class(&self, ty: &Ty) -> Option<usize> {
+        match ty {
+            Ty::Var(id) => Some(self.find(self.slots[*id])),
+            Ty::Mono(var) => self.named.get(var).map(|slot| self.find(*slot)),
+            _ => None,
+        }
+    }
+
+    /// The number of unification variables
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// The name the unification variable `id` is printed and quantified as
+    pub fn name(&self, id: VarId) -> TypeVar {
+        format!("t{}", id)
+    }
+
+    fn slot_name(&self, slot: Slot) -> TypeVar {
+        match &self.vars[slot] {
+            Ty::Var(id) => self.name(*id),
+            var => var.to_string(),
+        }
+    }
+
+    /// `ty` with every solved variable replaced by its solution, and every unsolved
+    /// one by the variable standing for its class
+    pub fn apply(&self, ty: Ty) -> Ty {
+        match ty {
+            Ty::Var(_) | Ty::Mono(_) => match self.class(&ty) {
+                Some(root) => match &self.solution[root] {
+                    Some(solution) => self.apply(solution.clone()),
+                    None => self.vars[self.repr[root]].clone(),
+                },
+                None => ty,
+            },
+            Ty::Arrow(a, b, effect) => Ty::Arrow(Box::new(self.apply(*a)), Box::new(self.apply(*b)), effect),
+            Ty::App(name, args) => Ty::App(name, args.into_iter().map(|arg| self.apply(arg)).collect()),
+            Ty::Forall(vars, body) => Ty::Forall(vars, Box::new(self.apply(*body))),
+            _ => ty,
+        }
+    }
+
+    /// Like `apply`, with the remaining unification variables named, for types leaving the checker
+    pub fn apply_named(&self, ty: Ty) -> Ty {
+        self.name_vars(self.apply(ty), &|_| true)
+    }
+
+    /// Replace the unification variables of `ty` that satisfy `named` by their names
+    fn name_vars(&self, ty: Ty, named: &impl Fn(VarId) -> bool) -> Ty {
+        match ty {
+            Ty::Var(id) if named(id) => Ty::Mono(self.name(id)),
+            Ty::Arrow(a, b, effect) => Ty::Arrow(
+                Box::new(self.name_vars(*a, named)),
+                Box::new(self.name_vars(*b, named)),
+                effect,
+            ),
+            Ty::App(name, args) => Ty::App(name, args.into_iter().map(|arg| self.name_vars(arg, named)).collect()),
+            Ty::Forall(vars, body) => Ty::Forall(vars, Box::new(self.name_vars(*body, named))),
+            _ => ty,
+        }
+    }
+
+    /// Whether the class `root` occurs in `ty`, through the solutions of its variables
+    pub fn occurs(&self, root: usize, ty: &Ty) -> bool {
+        let mut visited = HashSet::new();
+        self.occurs_in(root, ty, &mut visited)
+    }
+
+    fn occurs_in(&self, root: Slot, ty: &Ty, visited: &mut HashSet<Slot>) -> bool {
+        match ty {
+            Ty::Var(_) | Ty::Mono(_) => match self.class(ty) {
+                Some(class) if class == root => true,
+                // Each solution is searched once, however often it is shared
+                Some(class) if visited.insert(class) => match &self.solution[class] {
+                    Some(solution) => self.occurs_in(root, solution, visited),
+                    None => false,
+                },
+                _ => false,
+            },
+            Ty::Arrow(a, b, _) => self.occurs_in(root, a, visited) || self.occurs_in(root, b, visited),
+            Ty::App(_, args) => args.iter().any(|arg| self.occurs_in(root, arg, visited)),
+            Ty::Forall(_, body) => self.occurs_in(root, body, visited),
+            _ => false,
+        }
+    }
+
+    /// Solve the unsolved class `root` to `ty`, in which it must not occur. A variable
+    /// joins the class of `ty`, which keeps standing for both.
+    pub fn bind(&mut self, root: usize, ty: Ty) {
+        let level = self.level[root];
+
+        let Some(other_root) = self.class(&ty) else {
+            // The variables of the solution are now reachable wherever `root` is
+            self.lower(&ty, level, &mut HashSet::new());
+            self.solution[root] = Some(ty);
+            return;
+        };
+
+        if root == other_root {
+            return;
+        }
+
+        // Union by rank
+        let (child, parent) = if self.rank[root] < self.rank[other_root] {
+            (root, other_root)
+        } else {
+            (other_root, root)
+        };
+
+        if self.rank[child] == self.rank[parent] {
+            self.rank[parent] += 1;
+        }
+
+        self.parent[child].set(parent);
+        self.repr[parent] = self.repr[other_root];
+        self.solution[parent] = self.solution[other_root].take();
+        self.level[parent] = level.min(self.level[other_root]);
+    }
+
+    /// Bring the classes of the variables of `ty` up to `level`
+    fn lower(&mut self, ty: &Ty, level: usize, visited: &mut HashSet<Slot>) {
+        match ty {
+            Ty::Var(_) | Ty::Mono(_) => {
+                if let Some(class) = self.class(ty) && visited.insert(class) {
+                    self.level[class] = self.level[class].min(level);
+
+                    if let Some(solution) = self.solution[class].clone() {
+                        self.lower(&solution, level, visited);
+                    }
+                }
+            }
+            Ty::Arrow(a, b, _) => {
+                self.lower(a, level, visited);
+                self.lower(b, level, visited);
+            }
+            Ty::App(_, args) => args.iter().for_each(|arg| self.lower(arg, level, visited)),
+            Ty::Forall(_, body) => self.lower(body, level, visited),
+            _ => {}
+        }
+    }
+
+    /// Quantify the resolved type `ty` over its variables deeper than the current level,
+    /// returning their names and `ty` with its unification variables among them named
+    pub fn generalize(&self, ty: Ty) -> (Vec<TypeVar>, Ty) {
+        let mut vars = Vec::new();
+        self.deeper_vars(&ty, &mut vars);
+
+        let names = vars.iter().map(|slot| self.slot_name(*slot)).collect();
+        let ty = self.name_vars(ty, &|id| vars.contains(&self.slots[id]));
+        (names, ty)
+    }
+
+    fn deeper_vars(&self, ty: &Ty, vars: &mut Vec<Slot>) {
+        match ty {
+            Ty::Var(_) | Ty::Mono(_) => {
+                if let Some(class) = self.class(ty)
+                    && self.level[class] > self.current_level
+                    && !vars.contains(&self.repr[class])
+                {
+                    vars.push(self.repr[class]);
+                }
+            }
+            Ty::Arrow(a, b, _) => {
+                self.deeper_vars(a, vars);
+                self.deeper_vars(b, vars);
+            }
+            Ty::App(_, args) => args.iter().for_each(|arg| self.deeper_vars(arg, vars)),
+            Ty::Forall(_, body) => self.deeper_vars(body, vars),
+            _ => {}
+        }
+    }
+
+    /// Every variable that has been solved or unified into another one, with what it stands for
+    pub fn bindings(&self) -> impl Iterator<Item = (TypeVar, Ty)> + '_ {
+        (0..self.vars.len()).filter_map(|slot| {
+            let root = self.find(slot);
+
+            let ty = match &self.solution[root] {
+                Some(solution) => self.apply(solution.clone()),
+                None if self.repr[root] != slot => self.vars[self.repr[root]].clone(),
+                None => return None,
+            };
+
+            Some((self.slot_name(slot), ty))
+        })
+    }
+}
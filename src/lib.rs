@@ -0,0 +1,8 @@
+//! The MujicaLang compiler. The command line driver is `main.rs`, the
+//! benchmarks use the passes directly.
+
+pub mod util;
+// mod old_examples;
+pub mod frontend;
+pub mod core;
+pub mod backend;
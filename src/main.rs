@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use crate::backend::closure_conversion::ClosureProgram;
 use crate::backend::emit_imp::emit_imp;
 use crate::core::conversion::ast2knf::AST2KNF;
@@ -12,11 +12,7 @@ use crate::frontend::name_resolution::NameResolver;
 use crate::frontend::tyck::tyck::TypeChecker;
 use crate::util::pp::pretty_expr;
 
-mod util;
-// mod old_examples;
-mod frontend;
-mod core;
-mod backend;
+use mujica_lang::{backend, core, frontend, util};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
@@ -35,17 +31,74 @@ struct Args {
     /// Output executable name (only if --compile is set)
     #[arg(short, long, default_value = "a.out")]
     exec: String,
+
+    /// On a type error, report every location that contributes to it
+    #[arg(long)]
+    slice_errors: bool,
+
+    /// Write the inferred signature of every let binding to this file
+    #[arg(long, value_name = "FILE")]
+    emit_signatures: Option<PathBuf>,
+
+    /// Write the derivation of every typing judgement to this file, even if type checking fails
+    #[arg(long, value_name = "FILE")]
+    trace_derivation: Option<PathBuf>,
+
+    /// Format of the derivation written by --trace-derivation
+    #[arg(long, value_enum, default_value_t = TraceFormat::Json)]
+    trace_format: TraceFormat,
+
+    /// Disable a lint (repeatable)
+    #[arg(long, value_name = "LINT", value_parser = clap::builder::PossibleValuesParser::new(frontend::lint::LINTS))]
+    allow: Vec<String>,
+
+    /// Enable a lint that is off by default (repeatable)
+    #[arg(long, value_name = "LINT", value_parser = clap::builder::PossibleValuesParser::new(frontend::lint::LINTS))]
+    warn: Vec<String>,
+
+    /// Stop after type checking, without generating code
+    #[arg(long)]
+    check_only: bool,
+
+    /// Type checker to use
+    #[arg(long, value_enum, default_value_t = Checker::Bidirectional)]
+    checker: Checker,
+
+    /// Largest function body, in ANF expressions, inlined at its call sites (0 disables inlining)
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    inline_budget: usize,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Checker {
+    /// Bidirectional checking, with higher-rank types and typed holes
+    Bidirectional,
+    /// Constraint-based Hindley–Milner inference
+    Hm,
+    /// Run both checkers and report where they disagree, compiling the bidirectional result
+    Cross,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum TraceFormat {
+    Json,
+    /// A document of proof trees, typeset with the bussproofs package
+    Latex,
+    /// Nested lists, each rule above its premises
+    Markdown,
 }
 
 fn main() {
     let args = Args::parse();
 
-    let input_code = std::fs::read_to_string(&args.input)
-        .expect("Failed to read input source file");
+    let input_code = std::fs::read_to_string(&args.input).unwrap_or_else(|error| {
+        eprintln!("error: cannot read {}: {}", args.input.display(), error);
+        std::process::exit(1);
+    });
 
-    let c_code = compile_to_c(&input_code);
+    let c_code = compile_to_c(&input_code, &args);
 
-    std::fs::write(&args.output, c_code).expect("Failed to write output C file");
+    write_file(&args.output, c_code);
     println!("Generated C code saved to {}", args.output.display());
 
     if args.compile {
@@ -56,7 +109,10 @@ fn main() {
             .arg("-o")
             .arg(&args.exec)
             .status()
-            .expect("Failed to invoke GCC");
+            .unwrap_or_else(|error| {
+                eprintln!("error: cannot invoke gcc: {}", error);
+                std::process::exit(1);
+            });
 
         if status.success() {
             println!("Compilation succeeded. Executable: {}", args.exec);
@@ -66,35 +122,146 @@ fn main() {
     }
 }
 
-fn compile_to_c(input_code: &str) -> String {
+fn compile_to_c(input_code: &str, args: &Args) -> String {
     use crate::backend::closure_conversion::ClosureProgram;
     use crate::backend::emit_imp::emit_imp;
     use crate::core::conversion::ast2knf::AST2KNF;
     use crate::core::conversion::knf2anf::knf2anf;
     use crate::core::conversion::monomorphization::Monomorphization;
     use crate::frontend::name_resolution::NameResolver;
-    use crate::frontend::tyck::tyck::TypeChecker;
 
-    let ast = frontend::parse(input_code);
+    let program = frontend::desugar::desugar(frontend::parse(input_code).unwrap_or_else(|error| exit_with(error)));
     let mut name_resolver = NameResolver::new();
-    let resolved_ast = name_resolver.resolve(ast).unwrap();
+    let resolved_program = name_resolver.resolve_program(program).unwrap_or_else(|error| exit_with(error));
+
+    for warning in frontend::lint::run(&resolved_program, &args.allow, &args.warn) {
+        eprintln!("{}", warning);
+    }
+
+    let (mut typed_ast, methods) = type_check(resolved_program, args).unwrap_or_else(|error| exit_with(error));
+
+    let binding_types = frontend::effect::infer_effects(&mut typed_ast, &methods);
 
-    let mut type_checker = TypeChecker::new();
-    let typed_ast = type_checker.tyck(resolved_ast).unwrap();
+    if let Some(path) = &args.emit_signatures {
+        let interface = frontend::signatures::signatures(&typed_ast, &methods, &binding_types);
+        write_file(path, interface);
+    }
+
+    if args.check_only {
+        std::process::exit(0);
+    }
 
-    let uncurried_ast = core::uncurry::uncurry(typed_ast).unwrap();
+    let elaborated = frontend::tyck::elaborate::elaborate(typed_ast);
+    let uncurried_ast = core::uncurry::uncurry(elaborated).unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    });
 
     let mut ast2knf_conv = AST2KNF::new();
     let knf = ast2knf_conv.convert(uncurried_ast);
 
-    let anf = knf2anf(knf).unwrap();
+    let anf = knf2anf(knf).unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    });
 
-    let mut mono = Monomorphization::new();
-    mono.collect_instances(&anf);
-    let mono_anf = mono.rewrite_expr(anf, &mut std::collections::HashMap::new());
+    let mut mono = Monomorphization::with_methods(methods);
+    let mono_anf = mono.rewrite_expr(anf, &HashMap::new()).unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    });
+
+    let optimized_anf = core::optimize::inline::inline(mono_anf, args.inline_budget);
+    let optimized_anf = core::optimize::const_fold::fold_constants(optimized_anf);
+    let optimized_anf = core::optimize::dce::eliminate_dead_code(optimized_anf);
+
+    // Only functions the program still uses must be compilable
+    Monomorphization::check_rank1(&optimized_anf).unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    });
 
     let mut closure_conv = ClosureProgram::new();
-    closure_conv.convert(mono_anf);
+    closure_conv.convert(optimized_anf);
+    closure_conv.remove_unreferenced_globals();
+    closure_conv.resolve_known_calls();
 
     emit_imp(closure_conv)
-}
\ No newline at end of file
+}
+
+/// Write an output file, or report why it cannot be written and stop
+fn write_file(path: &std::path::Path, contents: String) {
+    std::fs::write(path, contents).unwrap_or_else(|error| {
+        eprintln!("error: cannot write {}: {}", path.display(), error);
+        std::process::exit(1);
+    });
+}
+
+/// Report an error of the frontend and stop: a typed hole report is printed as is
+fn exit_with(error: frontend::FrontendError) -> ! {
+    match error {
+        frontend::FrontendError::TypedHoles(report) => eprintln!("{}", report),
+        error => eprintln!("error: {}", error),
+    }
+    std::process::exit(1);
+}
+
+/// Type check with the checker selected by `--checker`, returning the typed program
+/// and the method implementations of its instances
+fn type_check(
+    program: frontend::name_resolution::ResolvedProgram,
+    args: &Args,
+) -> Result<(frontend::tyck::tyck::TypedASTExpr, frontend::tyck::type_class::MethodTable), frontend::FrontendError> {
+    use crate::frontend::hm::compare::disagreements;
+    use crate::frontend::hm::infer::HMChecker;
+    use crate::frontend::tyck::tyck::TypeChecker;
+
+    if args.checker == Checker::Hm {
+        if args.trace_derivation.is_some() {
+            eprintln!("--trace-derivation is only supported by the bidirectional checker");
+        }
+
+        let mut hm = HMChecker::new();
+        let typed_ast = hm.tyck(program)?;
+        return Ok((typed_ast, hm.method_table().clone()));
+    }
+
+    let hm_program = (args.checker == Checker::Cross).then(|| program.clone());
+
+    let mut type_checker = TypeChecker::with_error_slicing(args.slice_errors);
+    if args.trace_derivation.is_some() {
+        type_checker = type_checker.with_derivation();
+    }
+
+    let typed_ast = type_checker.tyck(program);
+
+    if let (Some(path), Some(derivations)) = (&args.trace_derivation, type_checker.derivations()) {
+        let trace = match args.trace_format {
+            TraceFormat::Json => derivations.to_json(),
+            TraceFormat::Latex => derivations.to_latex(),
+            TraceFormat::Markdown => derivations.to_markdown(),
+        };
+        write_file(path, trace);
+    }
+
+    if let Some(hm_program) = hm_program {
+        let mut hm = HMChecker::new();
+
+        match (&typed_ast, hm.tyck(hm_program)) {
+            (Ok(expected), Ok(found)) => {
+                for disagreement in disagreements(expected, &found) {
+                    eprintln!("Checkers disagree: {}", disagreement);
+                }
+            }
+            (Ok(_), Err(error)) => {
+                eprintln!("Checkers disagree: only the bidirectional checker accepts the program, hm reports: {}", error);
+            }
+            (Err(error), Ok(_)) => {
+                eprintln!("Checkers disagree: only the hm checker accepts the program, bidirectional reports: {}", error);
+            }
+            (Err(_), Err(_)) => {}
+        }
+    }
+
+    Ok((typed_ast?, type_checker.method_table().clone()))
+}
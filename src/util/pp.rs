@@ -38,11 +38,11 @@ pub fn pretty_expr<I: Display + Clone, T: TypeDisplay>(
 ) -> String {
     let pad = "  ".repeat(indent);
     match expr {
-        ASTExpr::Atom(atom, ty) => {
+        ASTExpr::Atom(atom, ty, _) => {
             let atom_str = pretty_atom(atom);
             format!("{}{}{}", pad, atom_str, ty.format_type())
         }
-        ASTExpr::If { cond, then, else_, ty } => {
+        ASTExpr::If { cond, then, else_, ty, .. } => {
             let cond_str = pretty_expr(cond, indent + 1);
             let then_str = pretty_expr(then, indent + 1);
             let else_str = pretty_expr(else_, indent + 1);
@@ -53,7 +53,7 @@ pub fn pretty_expr<I: Display + Clone, T: TypeDisplay>(
                 pad = pad
             )
         }
-        ASTExpr::Let { bind: (name, scheme), value, body, ty } => {
+        ASTExpr::Let { bind: (name, scheme), value, body, ty, .. } => {
             let name_str = match scheme {
                 Some(scheme) => format!("{} : {}", name, scheme),
                 None => name.to_string(),
@@ -68,7 +68,7 @@ pub fn pretty_expr<I: Display + Clone, T: TypeDisplay>(
                 pad = pad
             )
         }
-        ASTExpr::Apply { func, args, ty } => {
+        ASTExpr::Apply { func, args, ty, .. } => {
             let func_str = pretty_expr(func, 0);
             let args_str = pretty_expr(args, 0);
 
@@ -77,7 +77,7 @@ pub fn pretty_expr<I: Display + Clone, T: TypeDisplay>(
                 pad, func_str, args_str, ty.format_type()
             )
         }
-        ASTExpr::Lambda { arg: (name, ty), body, ret_ty } => {
+        ASTExpr::Lambda { arg: (name, ty), body, ret_ty, .. } => {
             let arg_type = ty.format_type();
             let body_str = pretty_expr(body, indent + 1);
 
@@ -98,6 +98,9 @@ pub fn pretty_atom<I: Display>(atom: &ASTAtom<I>) -> String {
         ASTAtom::Op(op) => pretty_op(op),
         ASTAtom::Unit => "()".to_string(),
         ASTAtom::Bool(bool) => bool.to_string(),
+        ASTAtom::Str(s) => format!("\"{}\"", s),
+        ASTAtom::Hole(None, _) => "_".to_string(),
+        ASTAtom::Hole(Some(name), _) => format!("?{}", name),
     }
 }
 
@@ -116,4 +119,46 @@ pub fn pretty_op(op: &OpType) -> String {
         Leq => "<=".to_string(),
         Geq => ">=".to_string(),
     }
-}
\ No newline at end of file
+}
+
+/// Print an expression on one line as it would be written in the source, without types.
+/// Identifiers are printed by `name`.
+pub fn pretty_source<I: Display, T, S>(expr: &ASTExpr<I, T, S>, name: &impl Fn(&I) -> String) -> String {
+    match expr {
+        ASTExpr::Atom(ASTAtom::Var(ident), _, _) => name(ident),
+        ASTExpr::Atom(atom, _, _) => pretty_atom(atom),
+        ASTExpr::If { cond, then, else_, .. } => format!(
+            "if {} then {} else {} end",
+            pretty_source(cond, name), pretty_source(then, name), pretty_source(else_, name)
+        ),
+        ASTExpr::Let { bind: (ident, _), value, body, .. } => format!(
+            "let {} = {} in {} end",
+            name(ident), pretty_source(value, name), pretty_source(body, name)
+        ),
+        ASTExpr::Apply { func, args, .. } => {
+            // Binary operators are applied to their operands one at a time
+            if let ASTExpr::Apply { func: op, args: left, .. } = &**func
+                && let ASTExpr::Atom(ASTAtom::Op(op), _, _) = &**op
+            {
+                return format!("{} {} {}", pretty_operand(left, name), pretty_op(op), pretty_operand(args, name));
+            }
+
+            let func = match **func {
+                ASTExpr::Atom(..) | ASTExpr::Apply { .. } => pretty_source(func, name),
+                _ => format!("({})", pretty_source(func, name)),
+            };
+
+            format!("{} {}", func, pretty_operand(args, name))
+        }
+        ASTExpr::Lambda { arg: (ident, _), body, .. } => {
+            format!("fun {} -> {}", name(ident), pretty_source(body, name))
+        }
+    }
+}
+
+fn pretty_operand<I: Display, T>(expr: &ASTExpr<I, T>, name: &impl Fn(&I) -> String) -> String {
+    match expr {
+        ASTExpr::Atom(..) => pretty_source(expr, name),
+        _ => format!("({})", pretty_source(expr, name)),
+    }
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::{check, error_with, run_with};
+
+const CHECKERS: [&str; 2] = ["bidirectional", "hm"];
+
+/// Both checkers accept `source`, and the cross check finds no disagreement
+fn agree(source: &str) {
+    let compilation = check(source, &["--checker", "cross"]);
+    assert!(compilation.success, "{}", compilation.stderr);
+    assert!(!compilation.stderr.contains("Checkers disagree"), "{}", compilation.stderr);
+}
+
+#[test]
+fn examples_type_the_same_with_both_checkers() {
+    for entry in std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/examples")).unwrap() {
+        let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        agree(&source);
+    }
+}
+
+#[test]
+fn generalized_bindings_run_the_same_with_both_checkers() {
+    let source = "
+        let pair = fun x y -> if x == x then y else y end in
+        let id = fun x -> x in
+        let b = pair true (id 1) in
+        pair (id false) (b + 41)
+        end end end";
+
+    agree(source);
+    for checker in CHECKERS {
+        assert_eq!(run_with(source, &["--checker", checker]), "42");
+    }
+}
+
+#[test]
+fn class_constraint_errors_are_the_same_with_both_checkers() {
+    let source = r#"
+class Show a where
+    show : a -> String
+end
+
+instance Show Int where
+    show = fun _x -> "int"
+end
+
+let describe : forall a. a -> String = fun x -> show x in describe 1 end
+"#;
+
+    for checker in CHECKERS {
+        let stderr = error_with(source, &["--checker", checker]);
+        assert!(stderr.contains(concat!(
+            "error: Cannot deduce (Show a) for rigid type variable a (bound by the annotation of describe), ",
+            "add the constraint to its annotation",
+        )), "{}: {}", checker, stderr);
+    }
+
+    let missing = source.replace("let describe : forall a. a -> String = fun x -> show x in describe 1 end", "show true");
+    for checker in CHECKERS {
+        let stderr = error_with(&missing, &["--checker", checker]);
+        assert!(stderr.contains("error: No instance for (Show bool)"), "{}: {}", checker, stderr);
+    }
+}
+
+#[test]
+fn instances_are_checked_against_the_class_with_both_checkers() {
+    let source = r#"
+class Show a where
+    show : a -> String
+end
+
+instance Show Int where
+    show = fun x -> x
+end
+
+show 1
+"#;
+
+    let stderr = error_with(source, &["--checker", "bidirectional"]);
+    assert!(stderr.contains(concat!(
+        "error: Type mismatch in method show of instance Show int:\n",
+        "  expected: string\n",
+        "            ^^^^^^\n",
+        "  found:    int\n",
+    )), "{}", stderr);
+
+    let stderr = error_with(source, &["--checker", "hm"]);
+    assert!(
+        stderr.contains("error: In method show of instance Show int: Cannot unify types: string and int"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn cross_check_reports_programs_only_one_checker_accepts() {
+    let compilation = check(
+        "let apply : (forall a. a -> a) -> Int = fun f -> f 1 in apply (fun x -> x) end",
+        &["--checker", "cross"],
+    );
+    assert!(compilation.stderr.contains(
+        "Checkers disagree: only the bidirectional checker accepts the program, hm reports: Higher-rank type"
+    ), "{}", compilation.stderr);
+}
+
+/// A function whose lets unify each value with the previous one, `depth` deep
+fn unified_chain(depth: usize) -> String {
+    let mut source = "let same = fun a _b -> a in\nlet f = fun x0 ->\n".to_string();
+    for i in 1..=depth {
+        source.push_str(&format!("let x{} = same x{} x{} in\n", i, i - 1, i - 1));
+    }
+    source.push_str(&format!("x{}\n{}in f 42\nend\nend\n", depth, "end\n".repeat(depth)));
+    source
+}
+
+#[test]
+fn long_chains_of_unified_variables_are_solved_by_both_checkers() {
+    agree(&unified_chain(300));
+    for checker in CHECKERS {
+        assert_eq!(run_with(&unified_chain(50), &["--checker", checker]), "42");
+    }
+}
+
+#[test]
+fn infinite_types_are_rejected_by_both_checkers() {
+    for checker in CHECKERS {
+        let stderr = error_with("let f = fun x -> x x in f end", &["--checker", checker]);
+        assert!(stderr.contains("infinite") || stderr.contains("occurs check"), "{}: {}", checker, stderr);
+    }
+}
+
+/// Whether `message` shows a type variable by its internal name, like `t3` or `a#0`
+fn shows_internal_names(message: &str) -> bool {
+    message.contains('#') || message.as_bytes().windows(2).any(|pair| {
+        pair[0] == b't' && pair[1].is_ascii_digit()
+    })
+}
+
+#[test]
+fn class_constraint_errors_use_readable_names_with_both_checkers() {
+    let class = "class Show a where\n    show : a -> String\nend\n\n";
+    let cases = [
+        (
+            "let f : forall a. a -> String = fun x -> show x in f 1 end",
+            "error: Cannot deduce (Show a) for rigid type variable a (bound by the annotation of f)",
+        ),
+        (
+            "let k = fun a _b -> a in k 1 (fun x -> show x) end",
+            "error: Ambiguous type variable 'a: cannot deduce (Show 'a), add it to the type annotation",
+        ),
+        (
+            "if (fun x -> x) == (fun x -> x) then 1 else 0 end",
+            "error: Values of function type 'a -> 'a cannot be compared: no instance for (Eq ('a -> 'a))",
+        ),
+        ("show (fun x -> x)", "error: No instance for (Show ('a -> 'a))"),
+    ];
+
+    for (body, message) in cases {
+        for checker in CHECKERS {
+            let stderr = error_with(&format!("{}{}", class, body), &["--checker", checker]);
+            assert!(stderr.contains(message), "{}: {}", checker, stderr);
+
+            let error = stderr.lines().find(|line| line.starts_with("error:")).unwrap();
+            assert!(!shows_internal_names(error), "{}: {}", checker, error);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Compile MujicaLang programs with the compiler binary, and run what it produces
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// How long a compiled program may run before it is considered to diverge
+const RUN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the compiler reported for a program
+pub struct Compilation {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+    dir: PathBuf,
+}
+
+/// What a compiled program did
+pub struct Run {
+    pub status: Option<ExitStatus>,
+    pub stdout: String,
+}
+
+impl Run {
+    pub fn succeeded(&self) -> bool {
+        self.status.is_some_and(|status| status.success())
+    }
+}
+
+/// A fresh directory for the files of one compilation
+fn scratch_dir() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = std::env::temp_dir().join(format!(
+        "mujica-test-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Compile `source` to C and then with GCC, passing `args` to the compiler
+pub fn compile(source: &str, args: &[&str]) -> Compilation {
+    let dir = scratch_dir();
+    let input = dir.join("main.ml");
+    std::fs::write(&input, source).unwrap();
+
+    // Relative paths in `args`, e.g. of `--emit-signatures`, name files next to the program
+    let output = Command::new(env!("CARGO_BIN_EXE_MujicaLang"))
+        .current_dir(&dir)
+        .arg(&input)
+        .arg("--output").arg(dir.join("main.c"))
+        .arg("--compile")
+        .arg("--exec").arg(dir.join("main"))
+        .args(args)
+        .output()
+        .unwrap();
+
+    // Only type checking produces no executable
+    let compiled = args.contains(&"--check-only") || dir.join("main").exists();
+
+    Compilation {
+        success: output.status.success() && compiled,
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        dir,
+    }
+}
+
+/// Type check `source` only, returning what the compiler reported
+pub fn check(source: &str, args: &[&str]) -> Compilation {
+    compile(source, &[&["--check-only"], args].concat())
+}
+
+impl Compilation {
+    /// The generated C code
+    pub fn c_code(&self) -> String {
+        std::fs::read_to_string(self.dir.join("main.c")).unwrap()
+    }
+
+    /// A file the compiler wrote next to the program, e.g. with `--emit-signatures`
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// Run the executable, killing it if it does not finish in time
+    pub fn run(&self) -> Run {
+        assert!(self.success, "compilation failed:\n{}", self.stderr);
+
+        let mut child = Command::new(self.dir.join("main"))
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let deadline = Instant::now() + RUN_TIMEOUT;
+        let status = loop {
+            if let Some(status) = child.try_wait().unwrap() {
+                break Some(status);
+            }
+            if Instant::now() > deadline {
+                child.kill().unwrap();
+                child.wait().unwrap();
+                break None;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        let stdout = std::io::read_to_string(child.stdout.take().unwrap()).unwrap();
+        Run { status, stdout }
+    }
+}
+
+impl Drop for Compilation {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Compile and run `source`, returning the printed result
+pub fn run(source: &str) -> String {
+    run_with(source, &[])
+}
+
+/// Compile `source` with the compiler flags `args` and run it, returning the printed result
+pub fn run_with(source: &str, args: &[&str]) -> String {
+    let run = compile(source, args).run();
+    assert!(run.succeeded(), "program failed with {:?}, printed {:?}", run.status, run.stdout);
+    run.stdout.trim_end().to_string()
+}
+
+/// Compile `source`, which must be rejected, returning the diagnostics
+pub fn error(source: &str) -> String {
+    error_with(source, &[])
+}
+
+pub fn error_with(source: &str, args: &[&str]) -> String {
+    rejected(compile(source, args))
+}
+
+/// Type check `source` only, which must be rejected, returning the diagnostics
+pub fn check_error(source: &str) -> String {
+    rejected(check(source, &[]))
+}
+
+fn rejected(compilation: Compilation) -> String {
+    assert!(!compilation.success, "expected a compile error, the program was accepted");
+    assert!(!compilation.stderr.contains("panicked"), "the compiler crashed:\n{}", compilation.stderr);
+    compilation.stderr.clone()
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use common::{check, check_error, error, error_with};
+
+#[test]
+fn type_errors_are_reported_without_crashing() {
+    let stderr = error("let f : Int -> Int = fun n -> n + true in f 1 end");
+    assert!(stderr.starts_with("error: Type mismatch in argument 2 of (+) at 1:31:"), "{}", stderr);
+}
+
+#[test]
+fn mismatches_underline_where_the_types_diverge() {
+    let stderr = check_error("let apply : (Int -> Bool) -> Int = fun _f -> 1 in let g : Int -> Int = fun x -> x in apply g end end");
+    assert!(stderr.contains(concat!(
+        "  expected: int -> bool\n",
+        "                   ^^^^\n",
+        "  found:    int -> int\n",
+        "                   ^^^\n",
+    )), "{}", stderr);
+}
+
+#[test]
+fn unification_variables_get_readable_names() {
+    let stderr = check_error("let f = fun x -> x x in 1 end");
+    assert!(stderr.contains("'a occurs in 'a -> 'b, so the type would be infinite"), "{}", stderr);
+}
+
+#[test]
+fn quantified_variables_are_named_like_their_occurrences() {
+    let stderr = check_error("let f : Int -> Int = fun g -> g in let h : (forall a. a -> a) -> Int = f in 1 end end");
+    assert!(stderr.contains("expected: (forall 'a. 'a -> 'a) -> int"), "{}", stderr);
+}
+
+#[test]
+fn holes_are_reported_with_their_location() {
+    let stderr = check_error("let n = 1 in n + ?rest end");
+    assert!(stderr.contains("Found hole ?rest at 1:18 : int\n  Relevant bindings include:\n    n : int"), "{}", stderr);
+}
+
+#[test]
+fn holes_only_offer_the_innermost_of_shadowed_bindings() {
+    let stderr = check_error("let x = 1 in let x = 2 in x + _ end end");
+    assert_eq!(stderr.matches("    x : int").count(), 1, "{}", stderr);
+}
+
+#[test]
+fn hole_types_use_readable_names() {
+    let stderr = check_error("let f = fun y -> ?goal in 1 end");
+    assert!(stderr.contains("Found hole ?goal at 1:18 : 'a"), "{}", stderr);
+    assert!(stderr.contains("    y : 'b"), "{}", stderr);
+}
+
+#[test]
+fn holes_are_reported_alongside_type_errors() {
+    let stderr = check_error("let g = fun k -> k + ?q in g true end");
+    assert!(stderr.contains("Found hole ?q at 1:22 : int"), "{}", stderr);
+    assert!(stderr.contains("error: Type mismatch in argument 1 of g"), "{}", stderr);
+}
+
+#[test]
+fn syntax_errors_are_reported_with_their_location() {
+    let stderr = error("let x = 1 in x");
+    assert!(stderr.starts_with("error: Unexpected end of input at 1:15, expected \"end\""), "{}", stderr);
+
+    let stderr = error("let x : Int -> = 1 in x end");
+    assert!(stderr.starts_with("error: Unexpected `=` at 1:16"), "{}", stderr);
+}
+
+#[test]
+fn out_of_range_literals_are_syntax_errors() {
+    let stderr = error("99999999999");
+    assert!(stderr.starts_with("error: Integer literal out of range"), "{}", stderr);
+}
+
+#[test]
+fn slices_cover_the_program_body() {
+    let stderr = error_with("let f = fun x -> x + 1 in f true end", &["--slice-errors"]);
+    assert!(stderr.contains(concat!(
+        "The error involves all of these locations:\n",
+        "  - argument 1 of (+) at 1:18\n",
+        "  - argument 1 of f at 1:27",
+    )), "{}", stderr);
+}
+
+#[test]
+fn slices_cover_instance_methods() {
+    let stderr = error_with(r#"
+class Show a where
+    show : a -> String
+end
+
+instance Show Int where
+    show = fun x -> let y = x in if y then "yes" else "no" end end
+end
+
+show 1
+"#, &["--slice-errors"]);
+    assert!(stderr.contains(concat!(
+        "The error involves all of these locations:\n",
+        "  - the binding of y at 7:21\n",
+        "  - the condition of the if at 7:34\n",
+        "  - method show of instance Show int",
+    )), "{}", stderr);
+}
+
+#[test]
+fn errors_without_a_slice_say_so() {
+    let stderr = error_with(r#"
+class Show a where
+    show : a -> String
+end
+
+instance Show Int where
+    show = fun _x -> "int"
+end
+
+show true
+"#, &["--slice-errors"]);
+    assert!(stderr.contains("error: No instance for (Show bool)\nNo slice is available for this error"), "{}", stderr);
+}
+
+#[test]
+fn default_lints_warn_without_rejecting_the_program() {
+    let compilation = check(&std::fs::read_to_string("examples/lints.ml").unwrap(), &[]);
+    assert!(compilation.success, "{}", compilation.stderr);
+    assert!(compilation.stderr.contains(
+        "warning[unused-variable] at 3:1: unused variable unused, prefix it with an underscore if this is intended"
+    ), "{}", compilation.stderr);
+    assert!(compilation.stderr.contains(
+        "warning[constant-condition] at 5:1: the condition is always true, the else branch is never taken"
+    ), "{}", compilation.stderr);
+    assert!(compilation.stderr.contains(
+        "warning[unconditional-recursion] at 1:1: spin calls itself on every path"
+    ), "{}", compilation.stderr);
+    assert!(compilation.stderr.contains("warning[shadowing] at 4:11: x shadows the binding at 2:1"), "{}", compilation.stderr);
+}
+
+#[test]
+fn lints_can_be_enabled_and_disabled() {
+    let source = "let x = 1 in let x = 2 in if true then x else 0 end end end";
+
+    let compilation = check(source, &["--allow", "constant-condition"]);
+    assert!(compilation.stderr.contains("warning[shadowing] at 1:14: x shadows the binding at 1:1"), "{}", compilation.stderr);
+    assert!(!compilation.stderr.contains("warning[constant-condition]"), "{}", compilation.stderr);
+
+    let compilation = check(source, &["--allow", "constant-condition", "--warn", "constant-condition"]);
+    assert!(compilation.stderr.contains("warning[constant-condition]"), "{}", compilation.stderr);
+
+    let allow_all = ["--allow", "unused-variable", "--allow", "shadowing", "--allow", "constant-condition"];
+    let compilation = check(source, &allow_all);
+    assert!(compilation.success && compilation.stderr.is_empty(), "{}", compilation.stderr);
+
+    let compilation = check(source, &["--allow", "no-such-lint"]);
+    assert!(!compilation.success && compilation.stderr.contains("invalid value 'no-such-lint'"), "{}", compilation.stderr);
+}
+
+#[test]
+fn unbound_variables_suggest_close_names() {
+    let stderr = check_error("let count = 1 in cont end");
+    assert!(stderr.contains("error: unbound variable cont\n  help: did you mean count?"), "{}", stderr);
+
+    let stderr = check_error("let total = 1 in let totl = 2 in totel end end");
+    assert!(stderr.contains("help: did you mean one of total, totl?"), "{}", stderr);
+
+    let stderr = check_error("let count = 1 in size end");
+    assert!(!stderr.contains("help:"), "{}", stderr);
+}
+
+#[test]
+fn unbound_variables_point_at_bindings_out_of_scope() {
+    let stderr = check_error("let a = later in let later = 1 in a end end");
+    assert!(stderr.contains("note: later is bound later, at 1:18, and is not visible before"), "{}", stderr);
+
+    let stderr = check_error("let g = (let inner = 1 in inner end) in inner end");
+    assert!(stderr.contains("note: inner is bound at 1:10, in a scope that does not enclose this use"), "{}", stderr);
+}
+
+#[test]
+fn only_annotated_lets_are_recursive() {
+    let source = "let r = fun (x: Int) -> x in let r = fun x -> r x in r 1 end end";
+    let compilation = check(source, &[]);
+    assert!(compilation.success, "{}", compilation.stderr);
+    assert!(!compilation.stderr.contains("warning[unconditional-recursion]"), "{}", compilation.stderr);
+    assert!(!compilation.stderr.contains("warning[unused-variable]"), "{}", compilation.stderr);
+
+    let stderr = check_error("let fact = fun n -> if n == 0 then 1 else n * fact (n - 1) end in fact 3 end");
+    assert!(stderr.contains(concat!(
+        "error: unbound variable fact\n",
+        "  note: fact is defined at 1:1, only a binding with a type annotation can refer to itself",
+    )), "{}", stderr);
+}
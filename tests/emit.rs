@@ -0,0 +1,108 @@
+mod common;
+
+use common::check;
+
+const PROGRAM: &str = "
+let id = fun x -> x in
+let twice = fun f x -> f (f x) in
+let n : Int = twice id 1 in
+n
+end end end";
+
+#[test]
+fn signatures_list_the_scheme_of_every_binding() {
+    let compilation = check(PROGRAM, &["--emit-signatures", "main.mli"]);
+    assert!(compilation.success, "{}", compilation.stderr);
+
+    let signatures = std::fs::read_to_string(compilation.path("main.mli")).unwrap();
+    assert_eq!(signatures, concat!(
+        "val id : forall a. a -> a\n",
+        "val twice : forall a. (a -> a) -> a -> a\n",
+        "val n : int\n",
+    ));
+}
+
+#[test]
+fn signatures_do_not_change_with_unrelated_bindings() {
+    let compilation = check(&format!("let pair = fun x y -> y in {} end", PROGRAM), &["--emit-signatures", "main.mli"]);
+    assert!(compilation.success, "{}", compilation.stderr);
+
+    let signatures = std::fs::read_to_string(compilation.path("main.mli")).unwrap();
+    assert!(signatures.starts_with("val pair : forall a b. a -> b -> b\n"), "{}", signatures);
+    assert!(signatures.contains("val twice : forall a. (a -> a) -> a -> a\n"), "{}", signatures);
+}
+
+#[test]
+fn local_signatures_share_the_names_of_their_definition() {
+    let source = "
+let outer = fun x -> let inner = fun y -> x in inner 1 end in
+let id : forall a. a -> a = fun (x: a) -> let same : a -> a = fun (y: a) -> y in same x end in
+id (outer true)
+end end";
+
+    let compilation = check(source, &["--emit-signatures", "main.mli"]);
+    assert!(compilation.success, "{}", compilation.stderr);
+
+    let signatures = std::fs::read_to_string(compilation.path("main.mli")).unwrap();
+    assert_eq!(signatures, concat!(
+        "val outer : forall a. a -> a\n",
+        "  val inner : forall b. b -> a\n",
+        "val id : forall a. a -> a\n",
+        "  val same : a -> a\n",
+    ));
+}
+
+
+/// The derivation of `source` written in `format`, checking that the compilation succeeded as expected
+fn trace(source: &str, format: &str, success: bool) -> String {
+    let compilation = check(source, &["--trace-derivation", "trace", "--trace-format", format]);
+    assert_eq!(compilation.success, success, "{}", compilation.stderr);
+    std::fs::read_to_string(compilation.path("trace")).unwrap()
+}
+
+#[test]
+fn derivations_record_judgements_and_unifications() {
+    let trace = trace("let f = fun x -> x + 1 in f 2 end", "markdown", true);
+    assert!(trace.contains("- **Let** Γ ⊢ `let f = fun x -> x + 1 in f 2 end` ⇒ `int`"), "{}", trace);
+    assert!(trace.contains("  - **Gen** `f : int -> int`"), "{}", trace);
+    assert!(trace.contains("- **Unify** `int` ~ `t0`\n"), "{}", trace);
+    assert!(trace.contains("- **Bind** `t0` := `int`"), "{}", trace);
+    assert!(trace.contains("| `t0` | `int` |"), "{}", trace);
+}
+
+#[test]
+fn derivations_are_written_when_type_checking_fails() {
+    let source = "let f = fun x -> x + 1 in f true end";
+
+    let markdown = trace(source, "markdown", false);
+    assert!(markdown.contains("- **Unify** `int` ~ `bool` — failed"), "{}", markdown);
+
+    let json = trace(source, "json", false);
+    assert!(json.contains("\"failed\": true") && json.contains("{ \"var\": \"t0\", \"type\": \"int\" }"), "{}", json);
+}
+
+#[test]
+fn derivations_typeset_as_proof_trees() {
+    let latex = trace(PROGRAM, "latex", true);
+    assert!(latex.starts_with("\\documentclass{article}\n\\usepackage{bussproofs}"), "{}", latex);
+    assert!(latex.contains("\\RightLabel{\\scriptsize \\textsc{Gen}}"), "{}", latex);
+    assert!(latex.trim_end().ends_with("\\end{document}"), "{}", latex);
+}
+
+#[test]
+fn signatures_show_the_effects_of_functions() {
+    let source = "
+let spin : Int -> Int = fun n -> if n == 0 then 0 else spin n end in
+let inc = fun n -> n + 1 in
+let later = fun n -> spin n in
+inc (later 0)
+end end end";
+
+    let compilation = check(source, &["--emit-signatures", "main.mli"]);
+    assert!(compilation.success, "{}", compilation.stderr);
+
+    let signatures = std::fs::read_to_string(compilation.path("main.mli")).unwrap();
+    assert!(signatures.contains("val spin : int -{div}-> int\n"), "{}", signatures);
+    assert!(signatures.contains("val inc : int -> int\n"), "{}", signatures);
+    assert!(signatures.contains("val later : int -{div}-> int\n"), "{}", signatures);
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use common::{check, error, error_with, run};
+
+const BOTH: &str = "let both : (forall a. a -> a) -> Int = fun f -> if f true then f 1 else 0 end in";
+
+#[test]
+fn higher_rank_arguments_type_check() {
+    let compilation = check(&format!("{} both (fun x -> x) end", BOTH), &[]);
+    assert!(compilation.success, "{}", compilation.stderr);
+}
+
+#[test]
+fn monomorphic_arguments_are_not_polymorphic_enough() {
+    let stderr = error(&format!("{} both (fun (x: Int) -> x) end", BOTH));
+    assert!(stderr.contains("a is a rigid type variable, bound by the polymorphic type forall a. a -> a"), "{}", stderr);
+}
+
+#[test]
+fn unused_higher_rank_functions_compile() {
+    assert_eq!(run(&format!("{} 42 end", BOTH)), "42");
+    assert_eq!(run(&format!("{} if 1 > 2 then both (fun x -> x) else 7 end end", BOTH)), "7");
+}
+
+#[test]
+fn calls_of_higher_rank_functions_are_reported() {
+    for budget in ["0", "10"] {
+        let stderr = error_with(&format!("{} both (fun x -> x) end", BOTH), &["--inline-budget", budget]);
+        assert!(stderr.contains("error: Cannot compile argument f of higher-rank type forall a. a -> a"), "{}", stderr);
+    }
+}
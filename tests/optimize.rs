@@ -0,0 +1,156 @@
+mod common;
+
+use common::{compile, run, run_with};
+
+const COMPOSE: &str = "
+let compose : forall a b c. (b -> c) -> (a -> b) -> a -> c = fun f g x -> f (g x) in
+    let inc : Int -> Int = fun x -> x + 1 in
+        let double = fun x -> x * 2 in
+            compose double inc 20
+        end
+    end
+end";
+
+#[test]
+fn inlining_preserves_results() {
+    assert_eq!(run(COMPOSE), "42");
+    assert_eq!(run_with(COMPOSE, &["--inline-budget", "0"]), "42");
+}
+
+#[test]
+fn inlined_functions_leave_no_closures() {
+    let inlined = compile(COMPOSE, &[]);
+    assert!(!inlined.c_code().contains("->func"), "{}", inlined.c_code());
+
+    let not_inlined = compile(COMPOSE, &["--inline-budget", "0"]);
+    assert!(not_inlined.c_code().contains("->func"));
+}
+
+#[test]
+fn recursive_functions_are_not_inlined() {
+    let source = "
+        let fact : Int -> Int = fun n -> if n == 0 then 1 else n * fact (n - 1) end in
+            fact 5
+        end";
+
+    assert_eq!(run(source), "120");
+}
+
+#[test]
+fn inlined_bindings_do_not_clash() {
+    let source = "
+        let add = fun x y -> let s = x + y in s end in
+            add (add 1 2) (add 3 4)
+        end";
+
+    assert_eq!(run(source), "10");
+}
+
+#[test]
+fn inlining_keeps_the_effects_of_function_arguments() {
+    // `spin` never returns: applying it through an inlined function must not be
+    // mistaken for a pure computation whose result is unused
+    let source = "
+        let spin : Int -> Int = fun n -> spin n in
+            let app = fun f y -> f y in
+                let x = app spin 1 in 5 end
+            end
+        end";
+
+    for budget in ["0", "10"] {
+        let run = compile(source, &["--inline-budget", budget]).run();
+        assert!(!run.succeeded(), "budget {}: the call of spin was dropped, printed {:?}", budget, run.stdout);
+    }
+}
+
+#[test]
+fn constants_are_folded() {
+    let compilation = compile("(2 + 3) * 4 * 5 - 8 + 17", &[]);
+    assert!(compilation.c_code().contains("= 109;"), "{}", compilation.c_code());
+    assert_eq!(compilation.run().stdout.trim_end(), "109");
+}
+
+#[test]
+fn constant_conditions_take_their_branch() {
+    let source = "
+        let x = 3 in
+            if x < 5 then x * 10 else x + 1 end
+        end";
+
+    let compilation = compile(source, &[]);
+    assert!(!compilation.c_code().contains("if ("), "{}", compilation.c_code());
+    assert_eq!(compilation.run().stdout.trim_end(), "30");
+}
+
+#[test]
+fn division_by_zero_is_left_to_fail_at_runtime() {
+    let run = compile("let x = 1 / 0 in 5 end", &[]).run();
+    assert!(!run.succeeded(), "the division was removed, printed {:?}", run.stdout);
+
+    let source = "
+        let div = fun a b -> a / b in
+            let x = div 1 0 in 5 end
+        end";
+    let run = compile(source, &[]).run();
+    assert!(!run.succeeded(), "the division was removed, printed {:?}", run.stdout);
+}
+
+#[test]
+fn unused_pure_bindings_are_removed() {
+    let source = "
+        let unused = fun x -> x * 1000 in
+            let y = 7 / 7 in 5 end
+        end";
+
+    let compilation = compile(source, &[]);
+    let c_code = compilation.c_code();
+    assert!(!c_code.contains("1000") && !c_code.contains("lambda_"), "{}", c_code);
+    assert_eq!(compilation.run().stdout.trim_end(), "5");
+}
+
+#[test]
+fn unused_divergent_bindings_are_kept() {
+    let source = "
+        let spin : Int -> Int = fun n -> if n == 0 then 0 else spin n end in
+            let _unused = spin 3 in 5 end
+        end";
+
+    let run = compile(source, &[]).run();
+    assert!(!run.succeeded(), "the divergent call was removed, printed {:?}", run.stdout);
+}
+
+#[test]
+fn known_functions_are_called_directly() {
+    let source = "
+        let fact : Int -> Int = fun n -> if n == 0 then 1 else n * fact (n - 1) end in
+            fact 10
+        end";
+
+    let compilation = compile(source, &[]);
+    let c_code = compilation.c_code();
+    assert!(c_code.contains("lambda_0(") && !c_code.contains("->func)("), "{}", c_code);
+    assert_eq!(compilation.run().stdout.trim_end(), "3628800");
+}
+
+#[test]
+fn unknown_functions_are_called_through_their_closure() {
+    let source = "
+        let apply : (Int -> Int) -> Int -> Int = fun f x -> f x in
+            let inc = fun x -> x + 1 in
+                let double = fun x -> x * 2 in
+                    apply (if 1 < 2 then inc else double end) (inc (double 20))
+                end
+            end
+        end";
+
+    let compilation = compile(source, &["--inline-budget", "0"]);
+    let c_code = compilation.c_code();
+    // Only the parameter `f` of apply is unknown
+    assert_eq!(c_code.matches("->func)(").count(), 1, "{}", c_code);
+    assert!(c_code.contains("->func)(f_"), "{}", c_code);
+    assert!(c_code.contains("= lambda_2(double_") && c_code.contains("= lambda_1(inc_"), "{}", c_code);
+    assert!(c_code.contains("= lambda_0(apply_"), "{}", c_code);
+    assert_eq!(compilation.run().stdout.trim_end(), "42");
+
+    assert_eq!(run(source), "42");
+}
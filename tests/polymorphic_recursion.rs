@@ -0,0 +1,82 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn polymorphic_recursion_runs() {
+    let source = std::fs::read_to_string("examples/polymorphic_recursion.ml").unwrap();
+    assert_eq!(run(&source), "42");
+}
+
+#[test]
+fn variables_the_recursion_keeps_are_not_boxed() {
+    // `b` is String in every call: the strings are compared, not the pointers to them
+    let source = r#"
+        let f : forall a b. (Ord b) => Int -> b -> b -> a -> Int = fun n x w y ->
+            if n == 0 then (if x < w then 10 else 20 end) else f (n - 1) x w (fun (z: a) -> z) end
+        in f 3 "a" "b" 5 end"#;
+
+    assert_eq!(run(source), "10");
+}
+
+#[test]
+fn kept_variables_are_specialized_for_each_use() {
+    let source = r#"
+        let f : forall a b. (Ord b) => Int -> b -> b -> a -> Int = fun n x w y ->
+            if n == 0 then (if x < w then 1 else 2 end) else f (n - 1) x w (fun (z: a) -> z) end
+        in f 3 "b" "a" true * 10 + f 2 1 2 5 end"#;
+
+    assert_eq!(run(source), "21");
+}
+
+#[test]
+fn class_methods_resolve_in_uniform_implementations() {
+    let source = r#"
+        class Show a where
+            show : a -> String
+        end
+
+        instance Show Int where
+            show = fun x -> "int"
+        end
+
+        instance Show Bool where
+            show = fun b -> if b then "true" else "false" end
+        end
+
+        let describe : forall a b. (Show b) => Int -> b -> a -> String = fun n x y ->
+            if n == 0 then show x else describe (n - 1) x (fun (z: a) -> z) end
+        in
+            let ignored = describe 2 5 1 in
+                describe 3 (1 == 1) 7
+            end
+        end"#;
+
+    assert_eq!(run(source), "true");
+}
+
+#[test]
+fn uniform_implementations_can_be_used_as_values() {
+    let source = "
+        let depth : forall b. Int -> b -> Int = fun n x ->
+            if n == 0 then 0 else 1 + depth (n - 1) (fun (y: b) -> y) end
+        in
+            let d = depth in d 5 true end
+        end";
+
+    assert_eq!(run(source), "5");
+}
+
+#[test]
+fn uniform_implementations_can_be_passed_as_arguments() {
+    let source = r#"
+        let depth : forall b. Int -> b -> Int = fun n x ->
+            if n == 0 then 0 else 1 + depth (n - 1) (fun (y: b) -> y) end
+        in
+            let app : (Int -> Bool -> Int) -> Int = fun f -> f 4 true in
+                app depth + (let d = depth in d 2 "s" end)
+            end
+        end"#;
+
+    assert_eq!(run(source), "6");
+}
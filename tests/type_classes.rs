@@ -0,0 +1,117 @@
+mod common;
+
+use std::collections::HashMap;
+use common::{check, error_with, run_with};
+use mujica_lang::core::{anf, Atom, TypedAtom};
+use mujica_lang::core::anf::CExpr;
+use mujica_lang::core::conversion::monomorphization::Monomorphization;
+use mujica_lang::frontend::name_resolution::ResolvedIdent;
+use mujica_lang::frontend::ty::{Effect, Ty};
+use mujica_lang::frontend::tyck::type_class::MethodTable;
+
+const SHOW: &str = r#"
+class Show a where
+    show : a -> String
+end
+
+instance Show Int where
+    show = fun x -> "int"
+end
+
+instance Show Bool where
+    show = fun b -> if b then "true" else "false" end
+end
+"#;
+
+const CHECKERS: [&str; 2] = ["bidirectional", "hm"];
+
+#[test]
+fn methods_resolve_to_the_instance_of_the_argument() {
+    let source = format!("{}
+        let describe : forall a. (Show a) => a -> String = fun x -> show x in
+            let ignored = describe 42 in
+                describe (1 == 1)
+            end
+        end", SHOW);
+
+    for checker in CHECKERS {
+        assert_eq!(run_with(&source, &["--checker", checker]), "true");
+    }
+}
+
+#[test]
+fn given_constraints_hold_in_nested_definitions() {
+    let source = format!("{}
+        let describe : forall a. (Show a) => a -> String = fun (x: a) ->
+            let inner : a -> String = fun (y: a) -> show y in inner x end
+        in describe 1 end", SHOW);
+
+    for checker in CHECKERS {
+        assert_eq!(run_with(&source, &["--checker", checker]), "int");
+    }
+}
+
+#[test]
+fn given_constraints_end_with_their_definition() {
+    let source = format!("{}
+        let describe : forall a. (Show a) => a -> String = fun x -> show x in
+            let other : forall b. b -> String = fun y -> show y in other 1 end
+        end", SHOW);
+
+    for checker in CHECKERS {
+        let stderr = error_with(&source, &["--checker", checker]);
+        assert!(stderr.contains("Cannot deduce (Show"), "{}: {}", checker, stderr);
+    }
+}
+
+#[test]
+fn missing_instances_are_reported() {
+    let source = format!(r#"{}
+        show "text""#, SHOW);
+
+    let compilation = check(&source, &[]);
+    assert!(!compilation.success);
+    assert!(compilation.stderr.contains("No instance for (Show string)"), "{}", compilation.stderr);
+}
+
+#[test]
+fn methods_at_types_that_stay_polymorphic_are_rejected() {
+    let programs = [
+        "fun x -> show x",
+        "let describe = fun x -> show x in describe end",
+        "let k = fun a _b -> a in k 1 show end",
+        "let k = fun a _b -> a in k 1 (fun x -> show x) end",
+    ];
+
+    for program in programs {
+        let source = format!("{}\n{}", SHOW, program);
+        for checker in CHECKERS {
+            let stderr = error_with(&source, &["--checker", checker]);
+            assert!(stderr.contains("error: Ambiguous type variable 'a"), "{}: {}", checker, stderr);
+        }
+    }
+}
+
+#[test]
+fn monomorphization_reports_methods_without_an_instance() {
+    let show = ResolvedIdent::new("show".to_string(), "show_0".to_string());
+    let a = Ty::Mono("a".to_string());
+
+    let mut methods = MethodTable::default();
+    methods.insert_method(&show, "a".to_string(), Ty::Arrow(Box::new(a.clone()), Box::new(Ty::String), Effect::PURE));
+    methods.insert_impl(&show.id, Ty::Int, ResolvedIdent::new("show".to_string(), "show_1".to_string()));
+
+    // A checker letting `show` through at a type variable
+    let call = anf::Expr::CExpr(CExpr::Apply {
+        func: TypedAtom {
+            atom: Atom::Var(show),
+            ty: Ty::Arrow(Box::new(a.clone()), Box::new(Ty::String), Effect::PURE),
+            ty_args: vec![],
+        },
+        args: vec![TypedAtom { atom: Atom::Int(1), ty: a, ty_args: vec![] }],
+        ty: Ty::String,
+    });
+
+    let error = Monomorphization::with_methods(methods).rewrite_expr(call, &HashMap::new()).unwrap_err();
+    assert_eq!(error.to_string(), "No instance of method show at type a -> string");
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::{compile, error, run};
+
+#[test]
+fn comparisons_are_specialized_per_type() {
+    assert_eq!(run(&std::fs::read_to_string("examples/compare.ml").unwrap()), "7");
+    assert_eq!(run("if (1 == 1) == (2 < 1) then 1 else 2 end"), "2");
+    assert_eq!(run(r#"if "abc" < "abd" then 1 else 2 end"#), "1");
+}
+
+#[test]
+fn functions_cannot_be_compared() {
+    let stderr = error("let f = fun (x: Int) -> x in if f == f then 1 else 0 end end");
+    assert!(stderr.contains("cannot be compared"), "{}", stderr);
+}
+
+#[test]
+fn let_bound_functions_are_generalized() {
+    assert_eq!(run(&std::fs::read_to_string("examples/generalize.ml").unwrap()), "42");
+    assert_eq!(run("let id = fun x -> x in if id true then id 1 else 0 end end"), "1");
+}
+
+#[test]
+fn other_bindings_are_not_generalized() {
+    let stderr = error("let f = fun x -> x in let g = f in if g true then g 1 else 0 end end end");
+    assert!(stderr.contains("expected: bool") && stderr.contains("found:    int"), "{}", stderr);
+}
+
+#[test]
+fn lambda_parameters_are_inferred() {
+    assert_eq!(run(&std::fs::read_to_string("examples/infer_lambda.ml").unwrap()), "42");
+    assert_eq!(run("let twice = fun f x -> f (f x) in twice (fun n -> n * 3) 5 end"), "45");
+}
+
+#[test]
+fn lambdas_checked_against_type_variables_keep_their_types() {
+    assert_eq!(run("let id = fun x -> x in let inc = id (fun y -> y + 1) in inc 41 end end"), "42");
+}
+
+#[test]
+fn arguments_are_checked_against_parameter_types() {
+    assert_eq!(run(&std::fs::read_to_string("examples/bidirectional.ml").unwrap()), "42");
+    assert_eq!(run("let apply : forall a b. (a -> b) -> a -> b = fun f x -> f x in apply (fun x -> x + 1) 5 end"), "6");
+}
+
+#[test]
+fn branches_are_checked_against_the_expected_type() {
+    let stderr = error("let f : Int -> Int = fun n -> if n > 0 then n else true end in f 1 end");
+    assert!(stderr.contains("expected: int") && stderr.contains("found:    bool"), "{}", stderr);
+}
+
+#[test]
+fn annotation_variables_scope_over_the_definition() {
+    assert_eq!(run(&std::fs::read_to_string("examples/scoped_tyvars.ml").unwrap()), "42");
+}
+
+#[test]
+fn annotation_variables_are_rigid() {
+    let stderr = error("let f : forall a. a -> Int = fun x -> x + 1 in f 1 end");
+    assert!(stderr.contains("a is a rigid type variable, bound by the annotation of f"), "{}", stderr);
+}
+
+#[test]
+fn type_constructors_can_be_applied() {
+    assert_eq!(run(&std::fs::read_to_string("examples/type_constructors.ml").unwrap()), "42");
+}
+
+#[test]
+fn type_constructors_are_kind_checked() {
+    let stderr = error("type Pair a b; let f : Pair Int -> Int = fun p -> 1 in 1 end");
+    assert!(stderr.contains("Type Pair int is missing arguments: it has kind * -> *, expected *"), "{}", stderr);
+
+    let stderr = error("let f : List Int -> Int = fun p -> 1 in 1 end");
+    assert!(stderr.contains("Unknown type List"), "{}", stderr);
+}
+
+#[test]
+fn operators_and_functions_of_several_parameters_are_desugared() {
+    assert_eq!(run("let f = fun a (b: Int) c -> a - b - c * 2 in f 20 (1 + 1) 3 end"), "12");
+    assert_eq!(run("((2 + 3)) * (10 - 4) / 3"), "10");
+}
+
+#[test]
+fn errors_refer_to_the_syntax_as_written() {
+    let stderr = error("let f = fun a b -> a + b in f 1 true end");
+    assert!(stderr.contains("error: Type mismatch in argument 2 of f at 1:29:"), "{}", stderr);
+
+    let stderr = error("1 + (2 * true)");
+    assert!(stderr.contains("error: Type mismatch in argument 2 of (*) at 1:6:"), "{}", stderr);
+}
+
+#[test]
+fn polymorphic_bindings_are_specialized_per_type_argument() {
+    let source = "
+        let id = fun x -> x in
+        let twice = fun f x -> f (f x) in
+        if twice id true then twice id 41 + 1 else 0 end
+        end end";
+
+    let compilation = compile(source, &["--inline-budget", "0"]);
+    let c_code = compilation.c_code();
+    assert!(c_code.contains("id__fn_int_to_int") && c_code.contains("id__fn_bool_to_bool"), "{}", c_code);
+    assert!(c_code.contains("twice__fn_fn_int_to_int_nf_to_fn_int_to_int_nf_nf"), "{}", c_code);
+    assert_eq!(compilation.run().stdout.trim_end(), "42");
+}
+
+#[test]
+fn undetermined_type_arguments_are_erased() {
+    assert_eq!(run("let k = fun a _b -> a in k 1 (fun x -> x) end"), "1");
+    assert_eq!(run("let id = fun x -> x in let _f = id id in 2 end end"), "2");
+    assert_eq!(run("let k : forall a b. a -> a = fun x -> x in k 3 end"), "3");
+}